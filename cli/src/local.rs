@@ -0,0 +1,67 @@
+//! Offline play: the session and wordle programs run in `gtest` inside the client.
+
+use crate::Session;
+use anyhow::{anyhow, bail, Result};
+use gstd::{ActorId, Decode};
+use gtest::{Program, System};
+use session_io::{Action, GameSessionInit, SessionEvent};
+
+/// The account the player plays from, which also deploys the programs.
+const PLAYER: u64 = 42;
+const SESSION_PROGRAM_ID: u64 = 1;
+const WORDLE_PROGRAM_ID: u64 = 2;
+const MAX_PLAY_TIMES: u32 = 6;
+
+pub struct LocalSession<'a> {
+    session: Program<'a>,
+}
+
+impl<'a> LocalSession<'a> {
+    /// Deploys the wordle program and the session using it, as built for the chain.
+    pub fn new(system: &'a System) -> Result<Self> {
+        let wordle = Program::from_binary_with_id(system, WORDLE_PROGRAM_ID, wordle::WASM_BINARY);
+        if wordle.send_bytes(PLAYER, []).main_failed() {
+            bail!("Failed to initialize the wordle program");
+        }
+        let session =
+            Program::from_binary_with_id(system, SESSION_PROGRAM_ID, session::WASM_BINARY);
+        let init_result = session.send(
+            PLAYER,
+            GameSessionInit {
+                wordle_address: WORDLE_PROGRAM_ID.into(),
+                max_play_times: MAX_PLAY_TIMES,
+            },
+        );
+        if init_result.main_failed() {
+            bail!("Failed to initialize the session");
+        }
+        //wordle只接受owner和添加的session
+        let add_result = wordle.send(
+            PLAYER,
+            wordle_io::Action::AddSession(SESSION_PROGRAM_ID.into()),
+        );
+        if add_result.main_failed() {
+            bail!("Failed to add the session to the wordle program");
+        }
+        Ok(Self { session })
+    }
+}
+
+impl Session for LocalSession<'_> {
+    fn player(&self) -> ActorId {
+        PLAYER.into()
+    }
+
+    async fn send(&mut self, action: Action) -> Result<SessionEvent> {
+        let result = self.session.send(PLAYER, action);
+        if result.main_failed() {
+            bail!("The session failed: {result:?}");
+        }
+        result
+            .log()
+            .iter()
+            .filter(|log| log.destination() == PLAYER.into())
+            .find_map(|log| SessionEvent::decode(&mut log.payload()).ok())
+            .ok_or_else(|| anyhow!("The session didn't reply"))
+    }
+}
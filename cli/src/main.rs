@@ -0,0 +1,215 @@
+//! Plays wordle in the terminal against the session program.
+//!
+//! ```text
+//! wordle-cli --session <address> [--node ws://127.0.0.1:9944] [--suri //Alice] [--language en]
+//! wordle-cli --local [--language en]
+//! ```
+//!
+//! With `--local` the programs run in `gtest` inside the client, so no node is needed.
+
+mod local;
+mod node;
+
+use anyhow::{anyhow, bail, Result};
+use gstd::ActorId;
+use scoring::Feedback;
+use session_io::{parse_positions, Action, GuessError, HardModeError, Language, SessionEvent};
+use std::io::{self, BufRead, Write};
+
+const USAGE: &str = "\
+Usage:
+    wordle-cli --session <address> [--node <url>] [--suri <suri>] [--language <en|de|es|ru>]
+    wordle-cli --local [--language <en|de|es|ru>]";
+
+const CORRECT: &str = "\x1b[30;42m";
+const CONTAINED: &str = "\x1b[30;43m";
+const MISS: &str = "\x1b[97;100m";
+const RESET: &str = "\x1b[0m";
+
+/// A session program the player sends actions to.
+trait Session {
+    /// The account the player plays from.
+    fn player(&self) -> ActorId;
+
+    /// Sends `action` and waits for the reply of the session.
+    async fn send(&mut self, action: Action) -> Result<SessionEvent>;
+}
+
+struct Args {
+    local: bool,
+    node: String,
+    suri: String,
+    session: Option<String>,
+    language: Language,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = Args {
+        local: false,
+        node: "ws://127.0.0.1:9944".to_string(),
+        suri: "//Alice".to_string(),
+        session: None,
+        language: Language::En,
+    };
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "--local" {
+            args.local = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| anyhow!("{arg} needs a value\n{USAGE}"))?;
+        match arg.as_str() {
+            "--node" => args.node = value,
+            "--suri" => args.suri = value,
+            "--session" => args.session = Some(value),
+            "--language" => args.language = parse_language(&value)?,
+            _ => bail!("Unknown argument {arg}\n{USAGE}"),
+        }
+    }
+    Ok(args)
+}
+
+fn parse_language(language: &str) -> Result<Language> {
+    Ok(match language {
+        "en" => Language::En,
+        "de" => Language::De,
+        "es" => Language::Es,
+        "ru" => Language::Ru,
+        _ => bail!("Unknown language {language}\n{USAGE}"),
+    })
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = parse_args()?;
+    if args.local {
+        let system = gtest::System::new();
+        let mut session = local::LocalSession::new(&system)?;
+        play(&mut session, args.language).await
+    } else {
+        let address = args
+            .session
+            .ok_or_else(|| anyhow!("--session is required without --local\n{USAGE}"))?;
+        let mut session = node::NodeSession::connect(&args.node, &args.suri, &address).await?;
+        play(&mut session, args.language).await
+    }
+}
+
+/// Starts a game and reads guesses from stdin until it's over or stdin ends.
+async fn play(session: &mut impl Session, language: Language) -> Result<()> {
+    let user = session.player();
+    let game_id = match session
+        .send(Action::StartGame {
+            user,
+            language,
+            backend: 0,
+        })
+        .await?
+    {
+        SessionEvent::GameStarted { game_id, .. } => game_id,
+        event => bail!("Failed to start a game: {event:?}"),
+    };
+    println!("Game {game_id} started. Enter your guesses, or an empty line to quit.");
+
+    let mut board: Vec<(String, Feedback)> = vec![];
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            break;
+        };
+        //和合约一样用小写比较
+        let word = line.trim().to_lowercase();
+        if word.is_empty() {
+            break;
+        }
+        let event = session
+            .send(Action::CheckWord {
+                user,
+                game_id,
+                word: word.clone(),
+            })
+            .await?;
+        match event {
+            SessionEvent::WordChecked {
+                correct_positions,
+                contained_in_word,
+                ..
+            } => {
+                let feedback = Feedback {
+                    correct_positions: parse_positions(&correct_positions)?,
+                    contained_in_word: parse_positions(&contained_in_word)?,
+                };
+                board.push((word, feedback));
+                print_board(&board);
+            }
+            //游戏结束的事件不带反馈, 用单词自己算出来
+            SessionEvent::UserWin { .. } => {
+                let feedback = scoring::score(&word, &word);
+                board.push((word, feedback));
+                print_board(&board);
+                println!("You won in {} guesses!", board.len());
+                return Ok(());
+            }
+            SessionEvent::UserLose { word: secret, .. } => {
+                let feedback = scoring::score(&secret, &word);
+                board.push((word, feedback));
+                print_board(&board);
+                println!("Out of attempts, the word was {secret}.");
+                return Ok(());
+            }
+            SessionEvent::InvalidGuess { error, .. } => match error {
+                GuessError::WrongLength { expected, .. } => {
+                    println!("The word must have {expected} letters.")
+                }
+                GuessError::InvalidCharacter { position } => {
+                    println!("Letter {} isn't in the alphabet.", position + 1)
+                }
+                GuessError::NotInWordList => println!("That word isn't in the word list."),
+                GuessError::HardMode(HardModeError::MissingCorrectLetter { position, letter }) => {
+                    println!("Letter {} must be {letter}.", position + 1)
+                }
+                GuessError::HardMode(HardModeError::MissingContainedLetter { letter }) => {
+                    println!("The word must contain {letter}.")
+                }
+            },
+            SessionEvent::Busy { .. } => {
+                println!("The previous guess is still being checked, try again.")
+            }
+            SessionEvent::RateLimited { error, .. } => println!("Too fast: {error:?}"),
+            SessionEvent::Paused { .. } => println!("The session is paused."),
+            SessionEvent::UnknownGame { .. } => bail!("The game {game_id} is already over"),
+            SessionEvent::BackendFailed { .. } => {
+                println!("The wordle program failed to check the guess, try again.")
+            }
+            event => bail!("Unexpected reply: {event:?}"),
+        }
+    }
+    Ok(())
+}
+
+fn print_board(board: &[(String, Feedback)]) {
+    println!();
+    for (word, feedback) in board {
+        let row: String = word
+            .chars()
+            .enumerate()
+            .map(|(i, letter)| {
+                let i = i as u8;
+                let color = if feedback.correct_positions.contains(&i) {
+                    CORRECT
+                } else if feedback.contained_in_word.contains(&i) {
+                    CONTAINED
+                } else {
+                    MISS
+                };
+                format!("{color} {letter} {RESET}")
+            })
+            .collect();
+        println!("{row}");
+    }
+    println!();
+}
@@ -0,0 +1,65 @@
+//! Play against the programs deployed on a node, through `gclient`.
+
+use crate::Session;
+use anyhow::{anyhow, Result};
+use gclient::{EventProcessor, GearApi, WSAddress};
+use gstd::{ActorId, Decode, Encode};
+use session_io::{Action, SessionEvent};
+
+/// The estimate only covers the message until the session waits for the wordle program, which
+/// then continues on the gas left.
+const GAS_MULTIPLIER: u64 = 2;
+
+pub struct NodeSession {
+    api: GearApi,
+    session: [u8; 32],
+    player: ActorId,
+}
+
+impl NodeSession {
+    /// Connects to the node at `node`, e.g. `ws://127.0.0.1:9944`, signing as `suri`, to play on
+    /// the session program at the hex `address`.
+    pub async fn connect(node: &str, suri: &str, address: &str) -> Result<Self> {
+        let (domain, port) = node
+            .rsplit_once(':')
+            .ok_or_else(|| anyhow!("The node URL {node} has no port"))?;
+        let api = GearApi::init_with(WSAddress::new(domain, port.parse::<u16>()?), suri).await?;
+        let session = hex::decode(address.trim_start_matches("0x"))?
+            .try_into()
+            .map_err(|_| anyhow!("The session address must be 32 bytes"))?;
+        let player = ActorId::new(api.account_id().clone().into());
+        Ok(Self {
+            api,
+            session,
+            player,
+        })
+    }
+}
+
+impl Session for NodeSession {
+    fn player(&self) -> ActorId {
+        self.player
+    }
+
+    async fn send(&mut self, action: Action) -> Result<SessionEvent> {
+        let payload = action.encode();
+        let gas_info = self
+            .api
+            .calculate_handle_gas(None, self.session.into(), payload.clone(), 0, true)
+            .await?;
+        let mut listener = self.api.subscribe().await?;
+        let (message_id, _) = self
+            .api
+            .send_message_bytes(
+                self.session.into(),
+                payload,
+                gas_info.min_limit * GAS_MULTIPLIER,
+                0,
+            )
+            .await?;
+        let (_, reply, _) = listener.reply_bytes_on(message_id).await?;
+        let reply = reply.map_err(|error| anyhow!("The session failed: {error}"))?;
+        SessionEvent::decode(&mut reply.as_slice())
+            .map_err(|error| anyhow!("Failed to decode the reply: {error}"))
+    }
+}
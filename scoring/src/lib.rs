@@ -0,0 +1,120 @@
+#![no_std]
+
+//! The rules of the game shared by the wordle and session programs: validating guesses,
+//! scoring them against the secret word and the hard mode constraints.
+
+extern crate alloc;
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+/// The feedback for a guess, as in `WordChecked` events of the wordle program.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Feedback {
+    /// Positions of the letters that are in the same position in the secret.
+    pub correct_positions: Vec<u8>,
+    /// Positions of the other letters that are contained somewhere in the secret.
+    pub contained_in_word: Vec<u8>,
+}
+
+impl Feedback {
+    /// Whether the guess was the secret itself.
+    pub fn is_solved(&self, word_length: usize) -> bool {
+        self.correct_positions.len() == word_length
+    }
+}
+
+/// Scores `guess` against `secret`.
+///
+/// Letters are compared as chars. A letter that isn't in its position is contained if the
+/// secret has it anywhere, no matter how many times either word repeats it. Only the positions
+/// both words have are scored.
+pub fn score(secret: &str, guess: &str) -> Feedback {
+    let mut feedback = Feedback::default();
+    for (i, (a, b)) in secret.chars().zip(guess.chars()).enumerate() {
+        if a == b {
+            feedback.correct_positions.push(i as u8);
+        } else if secret.contains(b) {
+            feedback.contained_in_word.push(i as u8);
+        }
+    }
+    feedback
+}
+
+/// Why a guess was rejected.
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+pub enum GuessError {
+    /// The guess doesn't have as many letters as the secret word.
+    WrongLength { expected: u8, actual: u32 },
+    /// The character at this position isn't in the alphabet of the game's language.
+    InvalidCharacter { position: u8 },
+    /// The word isn't in the list of allowed guesses of the wordle program.
+    NotInWordList,
+    /// The session is in hard mode and the guess doesn't use what earlier guesses revealed.
+    HardMode(HardModeError),
+}
+
+/// Checks a normalized guess for a secret of `word_length` letters of `alphabet`.
+///
+/// Lengths are counted in chars, so multibyte letters count once.
+pub fn validate_guess(word: &str, word_length: u8, alphabet: &str) -> Result<(), GuessError> {
+    let length = word.chars().count();
+    if length != word_length as usize {
+        return Err(GuessError::WrongLength {
+            expected: word_length,
+            actual: length as u32,
+        });
+    }
+    match word.chars().position(|c| !alphabet.contains(c)) {
+        Some(position) => Err(GuessError::InvalidCharacter {
+            position: position as u8,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Why a guess breaks the hard mode rules. Letters are strings of one char, which SCALE can't
+/// encode.
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+pub enum HardModeError {
+    /// A letter found in its position by an earlier guess isn't kept there.
+    MissingCorrectLetter { position: u8, letter: String },
+    /// A letter known to be in the secret isn't used.
+    MissingContainedLetter { letter: String },
+}
+
+/// Checks that `guess` uses everything the earlier guesses in `history` revealed: letters in
+/// the correct position stay there and letters contained in the secret are used somewhere.
+pub fn check_hard_mode<'a>(
+    history: impl IntoIterator<Item = (&'a str, &'a Feedback)>,
+    guess: &str,
+) -> Result<(), HardModeError> {
+    for (previous, feedback) in history {
+        for position in &feedback.correct_positions {
+            let Some(letter) = previous.chars().nth(*position as usize) else {
+                continue;
+            };
+            if guess.chars().nth(*position as usize) != Some(letter) {
+                return Err(HardModeError::MissingCorrectLetter {
+                    position: *position,
+                    letter: letter.to_string(),
+                });
+            }
+        }
+        for position in &feedback.contained_in_word {
+            let Some(letter) = previous.chars().nth(*position as usize) else {
+                continue;
+            };
+            if !guess.contains(letter) {
+                return Err(HardModeError::MissingContainedLetter {
+                    letter: letter.to_string(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
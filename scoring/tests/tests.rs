@@ -0,0 +1,129 @@
+use scoring::{check_hard_mode, score, validate_guess, Feedback, GuessError, HardModeError};
+
+const ALPHABET: [char; 3] = ['a', 'b', 'c'];
+
+fn feedback(correct_positions: &[u8], contained_in_word: &[u8]) -> Feedback {
+    Feedback {
+        correct_positions: correct_positions.to_vec(),
+        contained_in_word: contained_in_word.to_vec(),
+    }
+}
+
+/// Every word of `length` letters of [`ALPHABET`].
+fn all_words(length: u32) -> Vec<String> {
+    let size = ALPHABET.len();
+    (0..size.pow(length))
+        .map(|mut index| {
+            (0..length)
+                .map(|_| {
+                    let letter = ALPHABET[index % size];
+                    index /= size;
+                    letter
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn test_score_exhaustive() {
+    let words = all_words(4);
+    for secret in &words {
+        for guess in &words {
+            let feedback = score(secret, guess);
+            for (i, (a, b)) in secret.chars().zip(guess.chars()).enumerate() {
+                let i = i as u8;
+                assert_eq!(feedback.correct_positions.contains(&i), a == b);
+                assert_eq!(
+                    feedback.contained_in_word.contains(&i),
+                    a != b && secret.contains(b),
+                    "{secret} {guess}"
+                );
+            }
+            //位置按顺序排列
+            assert!(feedback.correct_positions.windows(2).all(|w| w[0] < w[1]));
+            assert!(feedback.contained_in_word.windows(2).all(|w| w[0] < w[1]));
+            assert_eq!(feedback.is_solved(4), secret == guess);
+        }
+        assert_eq!(score(secret, secret), feedback(&[0, 1, 2, 3], &[]));
+    }
+}
+
+#[test]
+fn test_score() {
+    assert_eq!(score("horse", "house"), feedback(&[0, 1, 3, 4], &[]));
+    assert_eq!(score("human", "house"), feedback(&[0], &[2]));
+    //重复的字母每一个都算包含
+    assert_eq!(score("human", "mamma"), feedback(&[2], &[0, 1, 3, 4]));
+    assert_eq!(score("küche", "kuche"), feedback(&[0, 2, 3, 4], &[]));
+    assert_eq!(score("слово", "место"), feedback(&[4], &[2]));
+    //长度不同时只比较共同的位置
+    assert_eq!(score("horse", "ho"), feedback(&[0, 1], &[]));
+}
+
+#[test]
+fn test_validate_guess() {
+    let alphabet = "abcdefghijklmnopqrstuvwxyz";
+    assert_eq!(validate_guess("house", 5, alphabet), Ok(()));
+    assert_eq!(
+        validate_guess("hous", 5, alphabet),
+        Err(GuessError::WrongLength {
+            expected: 5,
+            actual: 4,
+        })
+    );
+    assert_eq!(
+        validate_guess("hou5e", 5, alphabet),
+        Err(GuessError::InvalidCharacter { position: 3 })
+    );
+    //多字节字母只算一个
+    assert_eq!(
+        validate_guess("küche", 5, "abcdefghijklmnopqrstuvwxyzäöüß"),
+        Ok(())
+    );
+    assert_eq!(
+        validate_guess("küche", 5, alphabet),
+        Err(GuessError::InvalidCharacter { position: 1 })
+    );
+}
+
+#[test]
+fn test_hard_mode() {
+    let first = score("horse", "house");
+    let history = [("house", &first)];
+    assert_eq!(check_hard_mode(history, "horse"), Ok(()));
+    assert_eq!(
+        check_hard_mode(history, "mouse"),
+        Err(HardModeError::MissingCorrectLetter {
+            position: 0,
+            letter: "h".to_string(),
+        })
+    );
+
+    let second = score("human", "mouse");
+    let history = [("mouse", &second)];
+    assert_eq!(check_hard_mode(history, "human"), Ok(()));
+    assert_eq!(check_hard_mode(history, "unmet"), Ok(()));
+    assert_eq!(
+        check_hard_mode(history, "huban"),
+        Err(HardModeError::MissingContainedLetter {
+            letter: "m".to_string()
+        })
+    );
+    assert_eq!(check_hard_mode([], "hobby"), Ok(()));
+}
+
+#[test]
+fn test_hard_mode_exhaustive() {
+    //猜中答案总是符合困难模式
+    let words = all_words(3);
+    for secret in &words {
+        for guess in &words {
+            let feedback = score(secret, guess);
+            assert_eq!(
+                check_hard_mode([(guess.as_str(), &feedback)], secret),
+                Ok(())
+            );
+        }
+    }
+}
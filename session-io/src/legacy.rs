@@ -0,0 +1,163 @@
+//! The layouts of [`StateChunk`]s exported by earlier versions of the session program, decoded
+//! by [`StateChunk`]'s `Decode` implementation and upgraded to the current one.
+
+use super::*;
+
+/// Version 1, before backends. Games were identified by their id in the only wordle program.
+#[derive(Decode)]
+#[codec(crate = gstd::codec)]
+pub(crate) struct StateChunkV1 {
+    players: Vec<PlayerStateV1>,
+    next: Option<u32>,
+}
+
+#[derive(Decode)]
+#[codec(crate = gstd::codec)]
+struct PlayerStateV1 {
+    player: ActorId,
+    open_games: Vec<OpenGameStateV1>,
+    history: Vec<GameRecordV2>,
+}
+
+#[derive(Decode)]
+#[codec(crate = gstd::codec)]
+struct OpenGameStateV1 {
+    game_id: GameId,
+    language: Language,
+    word_length: u8,
+    guesses: Vec<GuessRecord>,
+}
+
+impl StateChunkV1 {
+    pub(crate) fn upgrade(self) -> StateChunk {
+        let next_game_id = self
+            .players
+            .iter()
+            .flat_map(|state| {
+                let open = state.open_games.iter().map(|game| game.game_id);
+                open.chain(state.history.iter().map(|record| record.game_id))
+            })
+            .max()
+            .map_or(0, |game_id| game_id + 1);
+        StateChunk {
+            version: 1,
+            backends: vec![],
+            next_game_id,
+            players: self
+                .players
+                .into_iter()
+                .map(|state| PlayerState {
+                    player: state.player,
+                    open_games: state
+                        .open_games
+                        .into_iter()
+                        .map(|game| OpenGameState {
+                            game_id: game.game_id,
+                            language: game.language,
+                            word_length: game.word_length,
+                            backend: 0,
+                            wordle_game_id: game.game_id,
+                            guesses: game.guesses,
+                            hints: vec![],
+                        })
+                        .collect(),
+                    history: state
+                        .history
+                        .into_iter()
+                        .map(GameRecordV2::upgrade)
+                        .collect(),
+                })
+                .collect(),
+            next: self.next,
+        }
+    }
+}
+
+/// Version 2, before hints.
+#[derive(Decode)]
+#[codec(crate = gstd::codec)]
+pub(crate) struct StateChunkV2 {
+    backends: Vec<(BackendId, ActorId)>,
+    next_game_id: GameId,
+    players: Vec<PlayerStateV2>,
+    next: Option<u32>,
+}
+
+#[derive(Decode)]
+#[codec(crate = gstd::codec)]
+struct PlayerStateV2 {
+    player: ActorId,
+    open_games: Vec<OpenGameStateV2>,
+    history: Vec<GameRecordV2>,
+}
+
+#[derive(Decode)]
+#[codec(crate = gstd::codec)]
+struct OpenGameStateV2 {
+    game_id: GameId,
+    language: Language,
+    word_length: u8,
+    backend: BackendId,
+    wordle_game_id: wordle_io::GameId,
+    guesses: Vec<GuessRecord>,
+}
+
+/// A finished game of versions 1 and 2.
+#[derive(Decode)]
+#[codec(crate = gstd::codec)]
+struct GameRecordV2 {
+    game_id: GameId,
+    secret: String,
+    guesses: Vec<GuessRecord>,
+    outcome: GameOutcome,
+    block_number: u32,
+}
+
+impl GameRecordV2 {
+    fn upgrade(self) -> GameRecord {
+        GameRecord {
+            game_id: self.game_id,
+            secret: self.secret,
+            guesses: self.guesses,
+            hints: vec![],
+            outcome: self.outcome,
+            block_number: self.block_number,
+        }
+    }
+}
+
+impl StateChunkV2 {
+    pub(crate) fn upgrade(self) -> StateChunk {
+        StateChunk {
+            version: 2,
+            backends: self.backends,
+            next_game_id: self.next_game_id,
+            players: self
+                .players
+                .into_iter()
+                .map(|state| PlayerState {
+                    player: state.player,
+                    open_games: state
+                        .open_games
+                        .into_iter()
+                        .map(|game| OpenGameState {
+                            game_id: game.game_id,
+                            language: game.language,
+                            word_length: game.word_length,
+                            backend: game.backend,
+                            wordle_game_id: game.wordle_game_id,
+                            guesses: game.guesses,
+                            hints: vec![],
+                        })
+                        .collect(),
+                    history: state
+                        .history
+                        .into_iter()
+                        .map(GameRecordV2::upgrade)
+                        .collect(),
+                })
+                .collect(),
+            next: self.next,
+        }
+    }
+}
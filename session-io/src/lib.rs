@@ -1,7 +1,12 @@
 #![no_std]
 
-use gmeta::{In, InOut, Metadata, Out};
+use core::num::ParseIntError;
+use gmeta::{In, InOut, Metadata};
 use gstd::{prelude::*, ActorId, MessageId};
+pub use scoring::{GuessError, HardModeError};
+pub use wordle_io::{Hint, Language};
+
+mod legacy;
 
 /// The contract metadata. Used by frontend apps & for describing the types of messages that can be
 /// sent in contract's entry points. See also [`Metadata`].
@@ -21,15 +26,25 @@ impl Metadata for SessionMetadata {
     /// Types for miscellaneous scenarios.
     type Others = ();
     /// The output type for the `handle_signal()` entry point.
-    type Signal = ();
+    type Signal = SessionSignal;
     /// I/O types for the `state()` entry point.
     ///
     /// You can also specify just an output ([`Out`]) or input ([`In`](gmeta::In)) type, if both
     /// ([`InOut`]) are expected like here.
-    type State = Out<State>;
+    type State = InOut<StateQuery, StateQueryReply>;
 }
 
-pub type State = Vec<(ActorId, u128)>;
+/// Identifier of a game, unique within one session.
+pub type GameId = u64;
+
+/// Identifier of a wordle program registered in the session.
+pub type BackendId = u32;
+
+/// The version of the layout of [`StateChunk`]s produced by this crate.
+///
+/// Bumped on every change of the exported state, so an upgraded program can tell which layout
+/// it imports.
+pub const SCHEMA_VERSION: u16 = 3;
 
 #[derive(Encode, Decode, TypeInfo)]
 #[codec(crate = gstd::codec)]
@@ -43,8 +58,114 @@ pub struct GameSessionInit {
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
 pub enum Action {
-    StartGame { user: ActorId },
-    CheckWord { user: ActorId, word: String },
+    StartGame {
+        user: ActorId,
+        language: Language,
+        /// The wordle program the game is played on.
+        backend: BackendId,
+    },
+    CheckWord {
+        user: ActorId,
+        game_id: GameId,
+        word: String,
+    },
+    /// Exports the state of up to `limit` players, starting from the `offset`th one.
+    ///
+    /// Only the owner can export the state.
+    ExportState { offset: u32, limit: u32 },
+    /// Imports a chunk of state exported from the previous version of the program.
+    ///
+    /// Games and finished games already imported are skipped, so a chunk can be sent again. A
+    /// game whose id another player already uses is rejected, and the history of each player is
+    /// cut to the most recent games that fit. Only backends this program doesn't have yet are
+    /// added, so the addresses it was set up with, e.g. backend 0 from `init()`, are kept.
+    ///
+    /// Only the owner can import the state.
+    ImportState(StateChunk),
+    /// Hands the program over to `new_owner`.
+    ///
+    /// Only the owner can transfer the ownership.
+    TransferOwnership { new_owner: ActorId },
+    /// Stops or resumes starting new games and making guesses. Pausing both is the emergency
+    /// stop.
+    ///
+    /// Only the owner can pause the program.
+    SetPaused { new_games: bool, guesses: bool },
+    /// Changes the number of guesses in games.
+    ///
+    /// Only the owner can change the limit.
+    SetMaxPlayTimes(u32),
+    /// Registers another wordle program players can start games on.
+    ///
+    /// Only the owner can add backends.
+    AddBackend(ActorId),
+    /// Points a backend to another wordle program. Games started on the previous one can only
+    /// be continued if its state was migrated to the new one.
+    ///
+    /// Only the owner can change the address.
+    SetBackendAddress {
+        backend: BackendId,
+        address: ActorId,
+    },
+    /// Changes the limits on how often players can play.
+    ///
+    /// Only the owner can change the limits.
+    SetRateLimits(RateLimits),
+    /// Ends an open game of `player` without recording it in the history, in the wordle program
+    /// too. A message of the player still waiting for the game is answered with
+    /// [`SessionEvent::GameForceEnded`].
+    ///
+    /// Only the owner can end games.
+    ForceEndGame { player: ActorId, game_id: GameId },
+    /// Reveals a letter of the secret word that wasn't guessed or revealed yet. A hint uses up
+    /// an attempt, so it can't be requested when only the last attempt is left, and it's rate
+    /// limited like a guess.
+    RequestHint { user: ActorId, game_id: GameId },
+    /// Replaces the gas reservation of an open game with a new one of `amount` gas valid for
+    /// `duration` blocks, paid from the gas of this message. Used when the reservation made at
+    /// the start of the game was spent, expired or didn't survive a migration.
+    ///
+    /// Only the owner can top up reservations.
+    TopUpReservation {
+        game_id: GameId,
+        amount: u64,
+        duration: u32,
+    },
+    /// Turns the hard mode on or off. In hard mode a guess must keep the letters earlier guesses
+    /// found in their position and use the letters they found in the word, or it's rejected with
+    /// [`GuessError::HardMode`].
+    ///
+    /// Only the owner can change the mode.
+    SetHardMode(bool),
+}
+
+/// The settings of the session, changed by its owner.
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct SessionConfig {
+    pub owner: ActorId,
+    pub backends: Vec<(BackendId, ActorId)>,
+    pub max_play_times: u32,
+    pub new_games_paused: bool,
+    pub guesses_paused: bool,
+    pub rate_limits: RateLimits,
+    pub hard_mode: bool,
+}
+
+/// Limits on how often each player can play, to slow bots down. A limit set to zero is
+/// disabled, which is the default.
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct RateLimits {
+    /// The number of blocks that must pass after a guess or hint before the player can guess
+    /// or ask for a hint again.
+    pub min_blocks_between_guesses: u32,
+    /// The number of games a player can start in an epoch.
+    pub max_games_per_epoch: u32,
+    /// The length of an epoch in blocks. Epochs start at multiples of it.
+    pub epoch_length: u32,
 }
 
 #[derive(Encode, Decode, TypeInfo, Clone, Debug)]
@@ -53,32 +174,305 @@ pub enum Action {
 pub enum SessionEvent {
     GameStarted {
         user: ActorId,
+        game_id: GameId,
     },
+    /// The positions are comma terminated lists, read by [`parse_positions`].
     WordChecked {
         user: ActorId,
+        game_id: GameId,
         correct_positions: String,
         contained_in_word: String,
     },
     UserWin {
         user: ActorId,
+        game_id: GameId,
+    },
+    UserLose {
+        user: ActorId,
+        game_id: GameId,
+        word: String,
+    },
+    /// The guess was rejected without consuming an attempt.
+    InvalidGuess {
+        user: ActorId,
+        game_id: GameId,
+        error: GuessError,
+    },
+    StateExported(StateChunk),
+    StateImported {
+        players: u32,
+    },
+    /// The action was rejected because the previous message of the player is still waiting for
+    /// the wordle program. It can be retried once that one is answered.
+    Busy {
+        user: ActorId,
+    },
+    /// The action was rejected because the player hit a rate limit. No attempt was used.
+    RateLimited {
+        user: ActorId,
+        error: RateLimitError,
+    },
+    /// The action was rejected because the owner paused it.
+    Paused {
+        user: ActorId,
+    },
+    OwnershipTransferred {
+        previous_owner: ActorId,
+        new_owner: ActorId,
+    },
+    /// The owner changed the settings, which are now as follows.
+    ConfigChanged(SessionConfig),
+    /// The reply to the owner, and to the message of the player waiting for the game if there
+    /// was one.
+    GameForceEnded {
+        player: ActorId,
+        game_id: GameId,
+    },
+    BackendAdded {
+        backend: BackendId,
+        address: ActorId,
+    },
+    /// The game wasn't started because there is no such backend.
+    UnknownBackend {
+        user: ActorId,
+        backend: BackendId,
+    },
+    HintRevealed {
+        user: ActorId,
+        game_id: GameId,
+        hint: Hint,
+    },
+    /// No attempt was used because every letter is already known or only the last attempt is
+    /// left.
+    NoHintAvailable {
+        user: ActorId,
+        game_id: GameId,
+    },
+    ReservationToppedUp(ReservationInfo),
+    /// A reply of a wordle program was ignored because no message was waiting for it, e.g. a
+    /// late or duplicated one, or it came from another program. Emitted as an event, i.e. sent
+    /// to the zero address, so replies can't fill the owner's mailbox.
+    ReplyDropped {
+        reply_to: MessageId,
+    },
+    /// The action was rejected because the player has no open game with this id, e.g. it's
+    /// already over.
+    UnknownGame {
+        user: ActorId,
+        game_id: GameId,
+    },
+    /// The wordle program failed to carry the action out, e.g. it panicked or replied with
+    /// something else than the action asks for. Nothing changed, so the action can be tried
+    /// again.
+    BackendFailed {
+        user: ActorId,
+        backend: BackendId,
     },
 }
 
+/// Sent to the player from `handle_signal()` when their message failed while waiting for the
+/// wordle program, e.g. because it ran out of gas.
+///
+/// The wordle program carries the action out anyway, so it isn't rolled back. Once the wordle
+/// program replies, the next message of the player receives the result of the action instead of
+/// being handled, and until then it's answered with [`SessionEvent::Busy`].
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum SessionSignal {
+    StartGamePending { user: ActorId },
+    GuessPending { user: ActorId, game_id: GameId },
+    HintPending { user: ActorId, game_id: GameId },
+}
+
+/// Gas reserved by the session for an open game, so it can still reply to the player when the
+/// waiting message runs low on gas.
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct ReservationInfo {
+    pub game_id: GameId,
+    pub amount: u64,
+    /// The last block in which the reservation can be used.
+    pub valid_until: u32,
+}
+
+/// Which of the [`RateLimits`] rejected an action.
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum RateLimitError {
+    /// The player guessed too recently and can guess again from `next_block`.
+    TooSoon { next_block: u32 },
+    /// The player started too many games in this epoch and can start another one from
+    /// `next_block`.
+    TooManyGames { next_block: u32 },
+}
+
+/// How a finished game ended.
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum GameOutcome {
+    Win,
+    Lose,
+}
+
+/// A guess together with the feedback the wordle program gave for it.
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct GuessRecord {
+    pub word: String,
+    pub correct_positions: Vec<u8>,
+    pub contained_in_word: Vec<u8>,
+}
+
+/// A finished game kept in the player's history.
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct GameRecord {
+    pub game_id: GameId,
+    pub secret: String,
+    pub guesses: Vec<GuessRecord>,
+    pub hints: Vec<Hint>,
+    pub outcome: GameOutcome,
+    /// The block in which the game finished.
+    pub block_number: u32,
+}
+
+impl GameRecord {
+    /// Renders the shareable result grid of the game. See [`share_grid`].
+    pub fn share_grid(&self) -> String {
+        share_grid(&self.guesses)
+    }
+}
+
+/// Renders guesses as the classic shareable grid, one row per guess: 🟩 for a letter in the
+/// correct position, 🟨 for a letter contained in the word and ⬛ for a miss.
+///
+/// Only the feedback is used, so the grid never reveals the guessed letters.
+pub fn share_grid(guesses: &[GuessRecord]) -> String {
+    let mut grid = String::new();
+    for (row, guess) in guesses.iter().enumerate() {
+        if row > 0 {
+            grid.push('\n');
+        }
+        for i in 0..guess.word.chars().count() as u8 {
+            grid.push(if guess.correct_positions.contains(&i) {
+                '🟩'
+            } else if guess.contained_in_word.contains(&i) {
+                '🟨'
+            } else {
+                '⬛'
+            });
+        }
+    }
+    grid
+}
+
+/// A game that hasn't finished yet, as it's stored in a [`StateChunk`].
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct OpenGameState {
+    pub game_id: GameId,
+    pub language: Language,
+    pub word_length: u8,
+    pub backend: BackendId,
+    /// The id of the game in the wordle program of its backend.
+    pub wordle_game_id: wordle_io::GameId,
+    pub guesses: Vec<GuessRecord>,
+    pub hints: Vec<Hint>,
+}
+
+/// Everything the session knows about a player, as it's stored in a [`StateChunk`].
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct PlayerState {
+    pub player: ActorId,
+    pub open_games: Vec<OpenGameState>,
+    /// Finished games, oldest first.
+    pub history: Vec<GameRecord>,
+}
+
+/// A part of the session state streamed from an old program into a new one.
+///
+/// Messages still waiting for a reply from the wordle program aren't migrated, so the program
+/// should be idle while its state is exported. Gas reservations belong to the old program and
+/// aren't migrated either, see [`Action::TopUpReservation`].
+///
+/// Chunks exported by earlier versions decode too: the version is decoded first and the rest of
+/// the chunk is upgraded from the layout of that version.
+#[derive(Encode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct StateChunk {
+    /// The [`SCHEMA_VERSION`] of the program that exported the chunk.
+    pub version: u16,
+    pub backends: Vec<(BackendId, ActorId)>,
+    /// The id the next started game gets.
+    pub next_game_id: GameId,
+    pub players: Vec<PlayerState>,
+    /// The offset of the next chunk, or [`None`] if this chunk is the last one.
+    pub next: Option<u32>,
+}
+
+impl Decode for StateChunk {
+    fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+        let version = u16::decode(input)?;
+        match version {
+            1 => Ok(legacy::StateChunkV1::decode(input)?.upgrade()),
+            2 => Ok(legacy::StateChunkV2::decode(input)?.upgrade()),
+            SCHEMA_VERSION => Ok(StateChunk {
+                version,
+                backends: Decode::decode(input)?,
+                next_game_id: Decode::decode(input)?,
+                players: Decode::decode(input)?,
+                next: Decode::decode(input)?,
+            }),
+            _ => Err("Unsupported state version".into()),
+        }
+    }
+}
+
 /// Queries the contract state.
 ///
 /// Used in the `state` crate.
-#[derive(Encode, Decode, TypeInfo)]
+#[derive(Encode, Decode, TypeInfo, Clone, Debug)]
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
 pub enum StateQuery {
-    /// Gets the list of actors who have [`ping`](PingPong::Ping)ed the contract.
+    /// Gets the games of the given [`ActorId`] that haven't finished yet.
+    ///
+    /// Returns [`StateQueryReply::PlayerGames`].
+    PlayerGames(ActorId),
+    /// Gets a finished game of the given [`ActorId`] from its history.
     ///
-    /// Returns [`StateQueryReply::Pingers`].
-    Pingers,
-    /// Gets the count of [`ping`](PingPong::Ping)s received from the given [`ActorId`].
+    /// Returns [`StateQueryReply::Game`].
+    Game { player: ActorId, game_id: GameId },
+    /// Gets a page of finished games of the given [`ActorId`], newest first.
     ///
-    /// Returns [`StateQueryReply::PingCount`].
-    PingCount(ActorId),
+    /// Returns [`StateQueryReply::History`].
+    History {
+        player: ActorId,
+        offset: u32,
+        limit: u32,
+    },
+    /// Gets the shareable result grid of a finished game of the given [`ActorId`].
+    ///
+    /// Returns [`StateQueryReply::ShareGrid`].
+    ShareGrid { player: ActorId, game_id: GameId },
+    /// Gets the settings of the session.
+    ///
+    /// Returns [`StateQueryReply::Config`].
+    Config,
+    /// Gets the gas reservations of open games, ordered by game id.
+    ///
+    /// Returns [`StateQueryReply::Reservations`].
+    Reservations,
 }
 
 /// The result of successfully processed [`StateQuery`].
@@ -88,8 +482,25 @@ pub enum StateQuery {
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
 pub enum StateQueryReply {
-    /// Returned from [`StateQuery::Pingers`].
-    Pingers(Vec<ActorId>),
-    /// Returned from [`StateQuery::PingCount`].
-    PingCount(u128),
+    /// Returned from [`StateQuery::PlayerGames`].
+    PlayerGames(Vec<GameId>),
+    /// Returned from [`StateQuery::Game`].
+    Game(Option<GameRecord>),
+    /// Returned from [`StateQuery::History`].
+    History(Vec<GameRecord>),
+    /// Returned from [`StateQuery::ShareGrid`].
+    ShareGrid(Option<String>),
+    /// Returned from [`StateQuery::Config`].
+    Config(SessionConfig),
+    /// Returned from [`StateQuery::Reservations`].
+    Reservations(Vec<ReservationInfo>),
+}
+
+/// Parses positions from the comma terminated list in [`SessionEvent::WordChecked`].
+pub fn parse_positions(positions: &str) -> Result<Vec<u8>, ParseIntError> {
+    positions
+        .split(',')
+        .filter(|position| !position.is_empty())
+        .map(str::parse)
+        .collect()
 }
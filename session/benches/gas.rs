@@ -0,0 +1,210 @@
+//! Gas burned by the session for each action, as word banks, players and histories grow.
+//!
+//! Run with `cargo bench -p session --bench gas`. Exits with an error if an action burns more
+//! than its budget in `benches/gas-budget.txt`, or in the file `GAS_BUDGET` points to. With
+//! `GAS_BUDGET_UPDATE=1` the budgets are written instead, from the most gas each action burned
+//! plus [`HEADROOM_PERCENT`].
+
+use gtest::{RunResult, System};
+use session_io::{Action, Language, SessionEvent};
+use std::{collections::BTreeMap, env, fs, process::ExitCode};
+use test_support::GameFixture;
+use wordle_io::{PackedWord, WordList, WORD_LENGTH};
+
+const PLAYER: u64 = 20;
+/// Other players get ids from here on.
+const FIRST_OTHER_PLAYER: u64 = 1_000;
+/// Each measured game ends after a wrong guess and a losing one.
+const MAX_PLAY_TIMES: u32 = 2;
+/// How much more gas than measured the written budgets allow.
+const HEADROOM_PERCENT: u64 = 20;
+
+const LANGUAGES: [Language; 4] = [Language::En, Language::De, Language::Es, Language::Ru];
+/// Words in the secret and allowed lists. Zero keeps the built-in bank and allows every guess.
+const BANK_SIZES: [u32; 3] = [0, 1_000, 20_000];
+/// Players with an open game, including the measured one.
+const PLAYER_COUNTS: [u64; 3] = [1, 10, 100];
+/// Finished games of the measured player. The last one fills the history.
+const HISTORY_LENS: [u32; 3] = [0, 10, 50];
+
+/// A word of the bank's length and alphabet that isn't a secret, so it never wins.
+fn wrong_guess(language: Language) -> &'static str {
+    match language {
+        Language::En => "crane",
+        Language::De => "katze",
+        Language::Es => "gatos",
+        Language::Ru => "рыбка",
+    }
+}
+
+/// `size` words of `language` spread evenly over all packed words, without `except`.
+fn generate_words(language: Language, size: u32, except: &str) -> Vec<PackedWord> {
+    let base = language.alphabet().chars().count() as PackedWord;
+    let step = base.pow(WORD_LENGTH as u32) / size;
+    let except = language.pack(except).expect("a word of the language");
+    (0..size)
+        .map(|i| i * step)
+        .filter(|word| *word != except)
+        .collect()
+}
+
+struct Measurement {
+    action: &'static str,
+    language: Language,
+    bank_size: u32,
+    players: u64,
+    history_len: u32,
+    gas: u64,
+}
+
+/// Plays a game of [`PLAYER`] after setting up the other players and the history, measuring
+/// each action of it.
+fn measure(language: Language, bank_size: u32, players: u64, history_len: u32) -> Vec<Measurement> {
+    let system = System::new();
+    let fixture = GameFixture::new(&system, MAX_PLAY_TIMES);
+    if bank_size > 0 {
+        let guess = wrong_guess(language);
+        let words = generate_words(language, bank_size, guess);
+        fixture.upload_words(language, WordList::Secrets, &words);
+        //猜错的单词也要允许
+        let mut allowed = words;
+        allowed.push(language.pack(guess).expect("a word of the language"));
+        fixture.upload_words(language, WordList::Allowed, &allowed);
+    }
+    for other in 1..players {
+        fixture.start(FIRST_OTHER_PLAYER + other);
+    }
+    for _ in 0..history_len {
+        fixture.start(PLAYER);
+        fixture.guess(PLAYER, "crane");
+        fixture.guess(PLAYER, "crane");
+    }
+
+    let measurement = |action, result: &RunResult| Measurement {
+        action,
+        language,
+        bank_size,
+        players,
+        history_len,
+        //等待的消息被唤醒后和wordle程序里烧掉的gas也算在内
+        gas: result.main_gas_burned().0
+            + result
+                .others_gas_burned()
+                .values()
+                .map(|gas| gas.0)
+                .sum::<u64>(),
+    };
+    let start_result = fixture.session.send(
+        PLAYER,
+        Action::StartGame {
+            user: PLAYER.into(),
+            language,
+            backend: 0,
+        },
+    );
+    let SessionEvent::GameStarted { game_id, .. } = test_support::reply(&start_result, PLAYER)
+    else {
+        panic!("Failed to start a game");
+    };
+    let mut measurements = vec![measurement("start_game", &start_result)];
+    //第一次猜错, 第二次是最后一次猜测, 游戏结束并存入历史
+    for action in ["check_word", "last_guess"] {
+        let result = fixture.session.send(
+            PLAYER,
+            Action::CheckWord {
+                user: PLAYER.into(),
+                game_id,
+                word: wrong_guess(language).to_string(),
+            },
+        );
+        test_support::reply(&result, PLAYER);
+        measurements.push(measurement(action, &result));
+    }
+    measurements
+}
+
+fn budget_path() -> String {
+    env::var("GAS_BUDGET")
+        .unwrap_or_else(|_| concat!(env!("CARGO_MANIFEST_DIR"), "/benches/gas-budget.txt").into())
+}
+
+/// Reads the `action gas` lines of the budget file, skipping comments.
+fn read_budget() -> BTreeMap<String, u64> {
+    let budget = fs::read_to_string(budget_path()).expect("Failed to read the gas budget");
+    budget
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (action, gas) = line.split_once(' ').expect("Budget lines are `action gas`");
+            let gas = gas.trim().parse().expect("Invalid gas in the budget");
+            (action.to_string(), gas)
+        })
+        .collect()
+}
+
+/// Writes the budget file with the most gas each action burned plus [`HEADROOM_PERCENT`],
+/// rounded up to millions.
+fn write_budget(burned: &BTreeMap<&str, u64>) {
+    let mut budget = format!(
+        "# The most gas the session may burn for each action, in any scenario of `benches/gas.rs`.\n\
+         # Written by `GAS_BUDGET_UPDATE=1 cargo bench -p session --bench gas`: the most gas the\n\
+         # action burned plus {HEADROOM_PERCENT}%. Update it when an optimization lands, so\n\
+         # regressions are caught.\n",
+    );
+    for (action, gas) in burned {
+        let limit = (gas * (100 + HEADROOM_PERCENT) / 100).div_ceil(1_000_000) * 1_000_000;
+        budget.push_str(&format!("{action} {limit}\n"));
+    }
+    fs::write(budget_path(), budget).expect("Failed to write the gas budget");
+}
+
+fn main() -> ExitCode {
+    let update = env::var("GAS_BUDGET_UPDATE").is_ok_and(|value| value == "1");
+    let budget = if update {
+        BTreeMap::new()
+    } else {
+        read_budget()
+    };
+    let mut burned: BTreeMap<&str, u64> = BTreeMap::new();
+    println!(
+        "{:<12} {:<8} {:>7} {:>7} {:>7} {:>15}",
+        "action", "language", "bank", "players", "history", "gas"
+    );
+    let mut over_budget = false;
+    for language in LANGUAGES {
+        for bank_size in BANK_SIZES {
+            for players in PLAYER_COUNTS {
+                for history_len in HISTORY_LENS {
+                    for row in measure(language, bank_size, players, history_len) {
+                        let max = burned.entry(row.action).or_default();
+                        *max = (*max).max(row.gas);
+                        let limit = budget.get(row.action).copied().unwrap_or(u64::MAX);
+                        let mark = if row.gas > limit { "  over budget" } else { "" };
+                        over_budget |= row.gas > limit;
+                        //派生的Debug不支持对齐
+                        let language = format!("{:?}", row.language);
+                        println!(
+                            "{:<12} {:<8} {:>7} {:>7} {:>7} {:>15}{mark}",
+                            row.action,
+                            language,
+                            row.bank_size,
+                            row.players,
+                            row.history_len,
+                            row.gas
+                        );
+                    }
+                }
+            }
+        }
+    }
+    if update {
+        write_budget(&burned);
+        return ExitCode::SUCCESS;
+    }
+    if over_budget {
+        eprintln!("Some actions burned more gas than their budget");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
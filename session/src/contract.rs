@@ -0,0 +1,1339 @@
+use gstd::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    debug, exec, msg,
+    prelude::*,
+    ActorId, MessageId, Reservation,
+};
+use scoring::Feedback;
+use session_io::SessionEvent::WordChecked;
+use session_io::*;
+use wordle_io::Event;
+
+//每个用户最多保存的历史游戏数量
+const MAX_HISTORY_LEN: usize = 50;
+//每局游戏开始时预留的gas, 足够在等待的消息gas不足时回复用户
+const RESERVATION_AMOUNT: u64 = 1_000_000_000;
+//预留的有效期, 按3秒一个区块大约是一天
+const RESERVATION_DURATION: u32 = 28_800;
+//被唤醒的消息剩余的gas少于这个数时用预留的gas回复
+const REPLY_GAS: u64 = 500_000_000;
+//等待中的消息留给handle_signal的gas
+const SIGNAL_GAS: u64 = 1_000_000_000;
+
+pub struct Session {
+    owner: ActorId,
+    //可选的wordle程序, 例如不同的语言或难度
+    backends: BTreeMap<BackendId, ActorId>,
+    next_backend_id: BackendId,
+    next_game_id: GameId,
+    player_game_status: HashMap<ActorId, GameStatus>,
+    //发给wordle的消息id -> 等待回复的用户和消息
+    pending_replies: HashMap<MessageId, PendingReply>,
+    //记录每局游戏的猜测次数
+    player_times: HashMap<GameId, Vec<GuessRecord>>,
+    //每局游戏用过的提示, 每个提示也算一次猜测
+    game_hints: HashMap<GameId, Vec<Hint>>,
+    //记录用户未结束的游戏
+    player_games: HashMap<ActorId, Vec<GameId>>,
+    //未结束的游戏的配置
+    game_configs: HashMap<GameId, GameConfig>,
+    //记录用户已结束的游戏, 最新的在最后
+    player_history: HashMap<ActorId, VecDeque<GameRecord>>,
+    //未结束的游戏预留的gas
+    reservations: HashMap<GameId, Reservation>,
+    max_play_times: u32,
+    //暂停开始新游戏
+    new_games_paused: bool,
+    //暂停猜测
+    guesses_paused: bool,
+    rate_limits: RateLimits,
+    //猜测必须用上之前猜测的反馈
+    hard_mode: bool,
+    //用户上一次猜测的区块
+    last_guess_blocks: HashMap<ActorId, u32>,
+    //用户最近一个周期和其中开始的游戏数量
+    epoch_games: HashMap<ActorId, (u32, u32)>,
+}
+
+/// A player's message waiting for the reply to a message sent to a wordle program.
+#[derive(Clone, Copy, Debug)]
+pub struct PendingReply {
+    player: ActorId,
+    //等待的消息失败以后是None, 回复由用户的下一条消息接收
+    origin_id: Option<MessageId>,
+    //消息发往的wordle程序, 之后改了backend的地址也只接受它的回复
+    wordle: ActorId,
+}
+
+#[derive(Clone, Debug)]
+pub struct GameConfig {
+    language: Language,
+    word_length: u8,
+    //游戏所在的wordle程序和它分配的id
+    backend: BackendId,
+    wordle_game_id: wordle_io::GameId,
+}
+
+impl Session {
+    fn assert_owner(&self, source: ActorId) {
+        assert_eq!(source, self.owner, "Only the owner can do this");
+    }
+
+    fn config(&self) -> SessionConfig {
+        SessionConfig {
+            owner: self.owner,
+            backends: self.backends(),
+            max_play_times: self.max_play_times,
+            new_games_paused: self.new_games_paused,
+            guesses_paused: self.guesses_paused,
+            rate_limits: self.rate_limits,
+            hard_mode: self.hard_mode,
+        }
+    }
+
+    /// Checks a guess against the earlier guesses of the game if the session is in hard mode.
+    fn check_hard_mode(&self, game_id: GameId, word: &str) -> Result<(), GuessError> {
+        if !self.hard_mode {
+            return Ok(());
+        }
+        let guesses = self
+            .player_times
+            .get(&game_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let feedback: Vec<Feedback> = guesses
+            .iter()
+            .map(|guess| Feedback {
+                correct_positions: guess.correct_positions.clone(),
+                contained_in_word: guess.contained_in_word.clone(),
+            })
+            .collect();
+        let history = guesses
+            .iter()
+            .zip(&feedback)
+            .map(|(guess, feedback)| (guess.word.as_str(), feedback));
+        scoring::check_hard_mode(history, word).map_err(GuessError::HardMode)
+    }
+
+    fn check_guess_rate(&self, player: ActorId) -> Result<(), RateLimitError> {
+        let min_blocks = self.rate_limits.min_blocks_between_guesses;
+        match self.last_guess_blocks.get(&player) {
+            Some(last_block) if min_blocks > 0 => {
+                let next_block = last_block.saturating_add(min_blocks);
+                if exec::block_height() < next_block {
+                    Err(RateLimitError::TooSoon { next_block })
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_game_rate(&self, player: ActorId) -> Result<(), RateLimitError> {
+        let RateLimits {
+            max_games_per_epoch,
+            epoch_length,
+            ..
+        } = self.rate_limits;
+        if max_games_per_epoch == 0 {
+            return Ok(());
+        }
+        let epoch = exec::block_height() / epoch_length;
+        match self.epoch_games.get(&player) {
+            Some((last_epoch, games)) if *last_epoch == epoch && *games >= max_games_per_epoch => {
+                Err(RateLimitError::TooManyGames {
+                    next_block: (epoch + 1).saturating_mul(epoch_length),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn count_game(&mut self, player: ActorId) {
+        let epoch_length = self.rate_limits.epoch_length;
+        if epoch_length == 0 {
+            return;
+        }
+        let epoch = exec::block_height() / epoch_length;
+        let (last_epoch, games) = self.epoch_games.entry(player).or_insert((epoch, 0));
+        if *last_epoch != epoch {
+            *last_epoch = epoch;
+            *games = 0;
+        }
+        *games += 1;
+    }
+
+    fn backends(&self) -> Vec<(BackendId, ActorId)> {
+        self.backends
+            .iter()
+            .map(|(backend, address)| (*backend, *address))
+            .collect()
+    }
+
+    fn add_backend(&mut self, address: ActorId) -> BackendId {
+        let backend = self.next_backend_id;
+        self.next_backend_id += 1;
+        self.backends.insert(backend, address);
+        backend
+    }
+
+    /// The number of attempts used in a game, counting both guesses and hints.
+    fn attempts(&self, game_id: GameId) -> u32 {
+        let guesses = self.player_times.get(&game_id).map_or(0, Vec::len);
+        let hints = self.game_hints.get(&game_id).map_or(0, Vec::len);
+        (guesses + hints) as u32
+    }
+
+    fn has_game(&self, player: ActorId, game_id: GameId) -> bool {
+        self.player_games
+            .get(&player)
+            .is_some_and(|games| games.contains(&game_id))
+    }
+
+    /// Removes an open game, returning its guesses and hints.
+    fn drop_game(&mut self, player: ActorId, game_id: GameId) -> (Vec<GuessRecord>, Vec<Hint>) {
+        if let Some(player_games) = self.player_games.get_mut(&player) {
+            player_games.retain(|id| *id != game_id);
+        }
+        self.game_configs.remove(&game_id);
+        if let Some(reservation) = self.reservations.remove(&game_id) {
+            //过期的预留已经无法取消, 忽略错误
+            if let Err(error) = reservation.unreserve() {
+                debug!("Failed to unreserve gas: {:?}", error);
+            }
+        }
+        (
+            self.player_times.remove(&game_id).unwrap_or_default(),
+            self.game_hints.remove(&game_id).unwrap_or_default(),
+        )
+    }
+
+    /// Parks the player until the `wordle` program replies to `send_id`.
+    fn await_reply(
+        &mut self,
+        player: ActorId,
+        wordle: ActorId,
+        send_id: MessageId,
+        status: GameStatus,
+    ) {
+        self.pending_replies.insert(
+            send_id,
+            PendingReply {
+                player,
+                origin_id: Some(msg::id()),
+                wordle,
+            },
+        );
+        self.player_game_status.insert(player, status);
+    }
+
+    /// Forgets what the player is waiting for, so a late reply to it is dropped.
+    fn clear_status(&mut self, player: ActorId) -> Option<GameStatus> {
+        let status = self.player_game_status.remove(&player)?;
+        self.pending_replies
+            .retain(|_, pending| pending.player != player);
+        Some(status)
+    }
+
+    /// Answers the message of the player waiting for the wordle program or about to receive its
+    /// reply with `event` instead, waking it if it's still waiting. Later replies of the wordle
+    /// program to it are dropped.
+    fn reject(&mut self, player: ActorId, event: SessionEvent) {
+        let pending = self
+            .pending_replies
+            .values()
+            .find(|pending| pending.player == player)
+            .copied();
+        let Some(status) = self.clear_status(player) else {
+            return;
+        };
+        let origin_id = match pending {
+            //等待的消息已经失败了就不用唤醒
+            Some(pending) => {
+                if let Some(origin_id) = pending.origin_id {
+                    exec::wake(origin_id).expect("Failed to wake");
+                }
+                pending.origin_id
+            }
+            None => status.woken_message(),
+        };
+        self.player_game_status
+            .insert(player, GameStatus::Rejected { origin_id, event });
+    }
+
+    /// Ignores a reply no message is waiting for, emitting it as an event.
+    fn drop_reply(&self, reply_to: MessageId) {
+        debug!("dropped the reply to {:?}", reply_to);
+        msg::send(ActorId::zero(), SessionEvent::ReplyDropped { reply_to }, 0)
+            .expect("Failed to send");
+    }
+
+    /// Undoes what the waiting message of `player` did before sending to the wordle program,
+    /// which failed to carry it out. Returns the backend of the wordle program.
+    fn roll_back(&mut self, player: ActorId, status: GameStatus) -> BackendId {
+        match status {
+            GameStatus::StartGameMessageSend {
+                backend,
+                reservation,
+                ..
+            } => {
+                if let Err(error) = reservation.unreserve() {
+                    debug!("Failed to unreserve gas: {:?}", error);
+                }
+                backend
+            }
+            GameStatus::CheckWordMessageSend {
+                game_id,
+                previous_guess_block,
+                ..
+            } => {
+                //去掉还没有反馈的猜测
+                if let Some(guesses) = self.player_times.get_mut(&game_id) {
+                    guesses.pop();
+                }
+                self.restore_guess_block(player, previous_guess_block);
+                self.game_configs[&game_id].backend
+            }
+            GameStatus::HintMessageSend {
+                game_id,
+                previous_guess_block,
+                ..
+            } => {
+                self.restore_guess_block(player, previous_guess_block);
+                self.game_configs[&game_id].backend
+            }
+            _ => unreachable!("Only waiting messages have pending replies"),
+        }
+    }
+
+    /// Puts back the block of the guess before the one rolled back, so it isn't rate limited.
+    fn restore_guess_block(&mut self, player: ActorId, block: Option<u32>) {
+        match block {
+            Some(block) => self.last_guess_blocks.insert(player, block),
+            None => self.last_guess_blocks.remove(&player),
+        };
+    }
+
+    /// Replies to the woken message of a game. If the message is running out of gas, the reply
+    /// is paid from the reservation of the game, which is used up.
+    fn reply_to_player(&mut self, game_id: GameId, event: SessionEvent) {
+        if exec::gas_available() < REPLY_GAS {
+            if let Some(reservation) = self.reservations.remove(&game_id) {
+                msg::reply_from_reservation(reservation.id(), event, 0)
+                    .expect("Failed to reply from reservation");
+                return;
+            }
+        }
+        msg::reply(event, 0).expect("Failed to reply");
+    }
+
+    fn reservations(&self) -> Vec<ReservationInfo> {
+        let mut reservations: Vec<ReservationInfo> = self
+            .reservations
+            .iter()
+            .map(|(game_id, reservation)| ReservationInfo {
+                game_id: *game_id,
+                amount: reservation.amount(),
+                valid_until: reservation.valid_until(),
+            })
+            .collect();
+        reservations.sort_unstable_by_key(|reservation| reservation.game_id);
+        reservations
+    }
+
+    fn finish_game(&mut self, player: ActorId, game_id: GameId, secret: String, win: bool) {
+        let (guesses, hints) = self.drop_game(player, game_id);
+        let record = GameRecord {
+            game_id,
+            secret,
+            guesses,
+            hints,
+            outcome: if win {
+                GameOutcome::Win
+            } else {
+                GameOutcome::Lose
+            },
+            block_number: exec::block_height(),
+        };
+        let history = self.player_history.entry(player).or_default();
+        if history.len() == MAX_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(record);
+    }
+
+    fn export_state(&self, offset: u32, limit: u32) -> StateChunk {
+        //按用户排序保证分块导出的顺序稳定
+        let mut players: Vec<ActorId> = self
+            .player_games
+            .keys()
+            .chain(self.player_history.keys())
+            .copied()
+            .collect();
+        players.sort_unstable();
+        players.dedup();
+        let end = players.len().min(offset.saturating_add(limit) as usize);
+        let chunk = players
+            .get(offset as usize..end)
+            .unwrap_or_default()
+            .iter()
+            .map(|player| PlayerState {
+                player: *player,
+                open_games: self
+                    .player_games
+                    .get(player)
+                    .map(|games| {
+                        games
+                            .iter()
+                            .map(|game_id| {
+                                let config = &self.game_configs[game_id];
+                                OpenGameState {
+                                    game_id: *game_id,
+                                    language: config.language,
+                                    word_length: config.word_length,
+                                    backend: config.backend,
+                                    wordle_game_id: config.wordle_game_id,
+                                    guesses: self
+                                        .player_times
+                                        .get(game_id)
+                                        .cloned()
+                                        .unwrap_or_default(),
+                                    hints: self
+                                        .game_hints
+                                        .get(game_id)
+                                        .cloned()
+                                        .unwrap_or_default(),
+                                }
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                history: self
+                    .player_history
+                    .get(player)
+                    .map(|history| history.iter().cloned().collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        StateChunk {
+            version: SCHEMA_VERSION,
+            backends: self.backends(),
+            next_game_id: self.next_game_id,
+            players: chunk,
+            next: (end < players.len()).then_some(end as u32),
+        }
+    }
+
+    fn import_state(&mut self, chunk: StateChunk) -> u32 {
+        let players = chunk.players.len() as u32;
+        //新程序已经有的backend保留自己的地址, 例如init设置的0号
+        for (backend, address) in chunk.backends {
+            self.backends.entry(backend).or_insert(address);
+            self.next_backend_id = self.next_backend_id.max(backend + 1);
+        }
+        self.next_game_id = self.next_game_id.max(chunk.next_game_id);
+        for state in chunk.players {
+            for game in state.open_games {
+                //重发的分块里已经导入或者已经结束的游戏跳过
+                if self.has_game(state.player, game.game_id)
+                    || self.find_record(state.player, game.game_id).is_some()
+                {
+                    continue;
+                }
+                assert!(
+                    !self.game_configs.contains_key(&game.game_id),
+                    "The game id is already used by another player"
+                );
+                self.player_games
+                    .entry(state.player)
+                    .or_default()
+                    .push(game.game_id);
+                self.game_configs.insert(
+                    game.game_id,
+                    GameConfig {
+                        language: game.language,
+                        word_length: game.word_length,
+                        backend: game.backend,
+                        wordle_game_id: game.wordle_game_id,
+                    },
+                );
+                self.player_times.insert(game.game_id, game.guesses);
+                self.game_hints.insert(game.game_id, game.hints);
+            }
+            let history = self.player_history.entry(state.player).or_default();
+            for record in state.history {
+                if history.iter().all(|known| known.game_id != record.game_id) {
+                    history.push_back(record);
+                }
+            }
+            //新程序上已经结束的游戏会排在导入的前面, 按结束的区块排序后只保留最近的
+            history
+                .make_contiguous()
+                .sort_by_key(|record| record.block_number);
+            while history.len() > MAX_HISTORY_LEN {
+                history.pop_front();
+            }
+        }
+        players
+    }
+
+    fn find_record(&self, player: ActorId, game_id: GameId) -> Option<&GameRecord> {
+        self.player_history
+            .get(&player)
+            .and_then(|history| history.iter().find(|record| record.game_id == game_id))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum GameStatus {
+    StartGameMessageSend {
+        origin_id: MessageId,
+        backend: BackendId,
+        reservation: Reservation,
+    },
+    //收到回复后origin_id是被唤醒的消息, 它失败以后是None, 由用户的下一条消息接收结果
+    StartGameMessageReceived {
+        origin_id: Option<MessageId>,
+        event: Event,
+        backend: BackendId,
+        reservation: Reservation,
+    },
+    //previous_guess_block是这次之前猜测的区块, wordle出错回滚时恢复
+    CheckWordMessageSend {
+        origin_id: MessageId,
+        game_id: GameId,
+        previous_guess_block: Option<u32>,
+    },
+    CheckWordMessageReceived {
+        origin_id: Option<MessageId>,
+        event: Event,
+        game_id: GameId,
+    },
+    HintMessageSend {
+        origin_id: MessageId,
+        game_id: GameId,
+        previous_guess_block: Option<u32>,
+    },
+    HintMessageReceived {
+        origin_id: Option<MessageId>,
+        event: Event,
+        game_id: GameId,
+    },
+    //不用等wordle的结果了, 被唤醒的消息直接回复event, 例如游戏被强制结束
+    Rejected {
+        origin_id: Option<MessageId>,
+        event: SessionEvent,
+    },
+}
+
+impl GameStatus {
+    /// The message of the player waiting for the wordle program, if any.
+    fn waiting_message(&self) -> Option<MessageId> {
+        match self {
+            GameStatus::StartGameMessageSend { origin_id, .. }
+            | GameStatus::CheckWordMessageSend { origin_id, .. }
+            | GameStatus::HintMessageSend { origin_id, .. } => Some(*origin_id),
+            _ => None,
+        }
+    }
+
+    /// The woken message the reply of the wordle program is for, if any.
+    fn woken_message(&self) -> Option<MessageId> {
+        match self {
+            GameStatus::StartGameMessageReceived { origin_id, .. }
+            | GameStatus::CheckWordMessageReceived { origin_id, .. }
+            | GameStatus::HintMessageReceived { origin_id, .. }
+            | GameStatus::Rejected { origin_id, .. } => *origin_id,
+            _ => None,
+        }
+    }
+
+    /// The game the message is guessing in or asking a hint for, if any.
+    fn game_id(&self) -> Option<GameId> {
+        match self {
+            GameStatus::CheckWordMessageSend { game_id, .. }
+            | GameStatus::CheckWordMessageReceived { game_id, .. }
+            | GameStatus::HintMessageSend { game_id, .. }
+            | GameStatus::HintMessageReceived { game_id, .. } => Some(*game_id),
+            _ => None,
+        }
+    }
+
+    /// Whether the message can't be handled because another message of the player is still
+    /// waiting for the wordle program or is about to receive its reply.
+    fn is_busy_for(&self, msg_id: MessageId) -> bool {
+        self.waiting_message().is_some()
+            || self
+                .woken_message()
+                .is_some_and(|origin_id| origin_id != msg_id)
+    }
+
+    /// Hands the reply over to the next message of the player, after the woken message failed.
+    fn orphan(&mut self) {
+        match self {
+            GameStatus::StartGameMessageReceived { origin_id, .. }
+            | GameStatus::CheckWordMessageReceived { origin_id, .. }
+            | GameStatus::HintMessageReceived { origin_id, .. }
+            | GameStatus::Rejected { origin_id, .. } => *origin_id = None,
+            _ => {}
+        }
+    }
+}
+
+static mut SESSION: Option<Session> = None;
+
+/// Parks the message until the wordle program replies. Gas is reserved for `handle_signal()`, so
+/// the session learns about the message failing while it waits.
+fn wait_for_wordle() -> ! {
+    exec::system_reserve_gas(SIGNAL_GAS).expect("Failed to reserve gas for the signal");
+    exec::wait()
+}
+
+/// Checks a normalized guess against the game it's made in.
+fn validate_guess(word: &str, config: &GameConfig) -> Result<(), GuessError> {
+    scoring::validate_guess(word, config.word_length, config.language.alphabet())
+}
+
+// The `init()` entry point.
+#[no_mangle]
+pub extern fn init() {
+    let game_session_init: GameSessionInit = msg::load().expect("Unable to decode GameSessionInit");
+    let mut session = Session {
+        owner: msg::source(),
+        backends: BTreeMap::new(),
+        next_backend_id: 0,
+        next_game_id: 0,
+        player_game_status: HashMap::new(),
+        pending_replies: HashMap::new(),
+        player_times: HashMap::new(),
+        game_hints: HashMap::new(),
+        player_games: Default::default(),
+        game_configs: Default::default(),
+        player_history: Default::default(),
+        reservations: HashMap::new(),
+        max_play_times: game_session_init.max_play_times,
+        new_games_paused: false,
+        guesses_paused: false,
+        rate_limits: RateLimits::default(),
+        hard_mode: false,
+        last_guess_blocks: HashMap::new(),
+        epoch_games: HashMap::new(),
+    };
+    //初始化时的wordle程序是0号
+    session.add_backend(game_session_init.wordle_address);
+    unsafe { SESSION = Some(session) }
+}
+
+/// Whether a player sends `action` to play, rather than the owner to manage the session.
+fn is_player_action(action: &Action) -> bool {
+    matches!(
+        action,
+        Action::StartGame { .. } | Action::CheckWord { .. } | Action::RequestHint { .. }
+    )
+}
+
+/// Handles an action only the owner can do, whatever the status of the owner's own games.
+fn handle_owner_action(session: &mut Session, user_id: ActorId, action: Action) {
+    match action {
+        Action::ExportState { offset, limit } => {
+            session.assert_owner(user_id);
+            let chunk = session.export_state(offset, limit);
+            msg::reply(SessionEvent::StateExported(chunk), 0).expect("Failed to reply");
+        }
+        Action::ImportState(chunk) => {
+            session.assert_owner(user_id);
+            let players = session.import_state(chunk);
+            msg::reply(SessionEvent::StateImported { players }, 0).expect("Failed to reply");
+        }
+        Action::TransferOwnership { new_owner } => {
+            session.assert_owner(user_id);
+            session.owner = new_owner;
+            msg::reply(
+                SessionEvent::OwnershipTransferred {
+                    previous_owner: user_id,
+                    new_owner,
+                },
+                0,
+            )
+            .expect("Failed to reply");
+        }
+        Action::SetPaused { new_games, guesses } => {
+            session.assert_owner(user_id);
+            session.new_games_paused = new_games;
+            session.guesses_paused = guesses;
+            msg::reply(SessionEvent::ConfigChanged(session.config()), 0).expect("Failed to reply");
+        }
+        Action::SetMaxPlayTimes(max_play_times) => {
+            session.assert_owner(user_id);
+            session.max_play_times = max_play_times;
+            msg::reply(SessionEvent::ConfigChanged(session.config()), 0).expect("Failed to reply");
+        }
+        Action::SetRateLimits(rate_limits) => {
+            session.assert_owner(user_id);
+            assert!(
+                rate_limits.max_games_per_epoch == 0 || rate_limits.epoch_length > 0,
+                "Games can only be limited in epochs of at least one block"
+            );
+            session.rate_limits = rate_limits;
+            msg::reply(SessionEvent::ConfigChanged(session.config()), 0).expect("Failed to reply");
+        }
+        Action::SetHardMode(hard_mode) => {
+            session.assert_owner(user_id);
+            session.hard_mode = hard_mode;
+            msg::reply(SessionEvent::ConfigChanged(session.config()), 0).expect("Failed to reply");
+        }
+        Action::AddBackend(address) => {
+            session.assert_owner(user_id);
+            let backend = session.add_backend(address);
+            msg::reply(SessionEvent::BackendAdded { backend, address }, 0)
+                .expect("Failed to reply");
+        }
+        Action::SetBackendAddress { backend, address } => {
+            session.assert_owner(user_id);
+            let wordle = session
+                .backends
+                .get_mut(&backend)
+                .expect("There is no such backend");
+            *wordle = address;
+            msg::reply(SessionEvent::ConfigChanged(session.config()), 0).expect("Failed to reply");
+        }
+        Action::ForceEndGame { player, game_id } => {
+            session.assert_owner(user_id);
+            assert!(
+                session.has_game(player, game_id),
+                "The player has no such game"
+            );
+            let event = SessionEvent::GameForceEnded { player, game_id };
+            //等待这局游戏的消息也收到结束的回复
+            let status = session.player_game_status.get(&player);
+            if status.and_then(GameStatus::game_id) == Some(game_id) {
+                session.reject(player, event.clone());
+            }
+            //wordle里的游戏也结束, 它的回复没有消息等待, 会被丢弃
+            let config = &session.game_configs[&game_id];
+            msg::send(
+                session.backends[&config.backend],
+                wordle_io::Action::EndGame {
+                    user: player,
+                    game_id: config.wordle_game_id,
+                },
+                0,
+            )
+            .expect("Failed to send");
+            session.drop_game(player, game_id);
+            msg::reply(event, 0).expect("Failed to reply");
+        }
+        Action::TopUpReservation {
+            game_id,
+            amount,
+            duration,
+        } => {
+            session.assert_owner(user_id);
+            assert!(
+                session.game_configs.contains_key(&game_id),
+                "There is no such game"
+            );
+            let reservation =
+                Reservation::reserve(amount, duration).expect("Failed to reserve gas");
+            if let Some(previous) = session.reservations.insert(game_id, reservation) {
+                if let Err(error) = previous.unreserve() {
+                    debug!("Failed to unreserve gas: {:?}", error);
+                }
+            }
+            msg::reply(
+                SessionEvent::ReservationToppedUp(ReservationInfo {
+                    game_id,
+                    amount,
+                    valid_until: reservation.valid_until(),
+                }),
+                0,
+            )
+            .expect("Failed to reply");
+        }
+        _ => unreachable!("Player actions are handled in handle()"),
+    }
+}
+
+// The `handle()` entry point.
+#[no_mangle]
+extern fn handle() {
+    let user_id = msg::source();
+    let session = unsafe { SESSION.as_mut().expect("State isn't initialized") };
+    let msg_id = msg::id();
+
+    let user_action: Action = msg::load().expect("Failed to load payload");
+    //owner的操作不受自己游戏状态的影响, 紧急暂停和强制结束随时都能执行
+    if !is_player_action(&user_action) {
+        handle_owner_action(session, user_id, user_action);
+        return;
+    }
+    let player_game_status = session.player_game_status.get(&user_id);
+
+    debug!(
+        "session.player_game_status.get(&msg::source()) is:{:?}",
+        session.player_game_status.get(&user_id)
+    );
+    if player_game_status.is_none() {
+        match user_action.clone() {
+            Action::StartGame {
+                user: _,
+                language,
+                backend,
+            } => {
+                if session.new_games_paused {
+                    msg::reply(SessionEvent::Paused { user: user_id }, 0).expect("Failed to reply");
+                    return;
+                }
+                if let Err(error) = session.check_game_rate(user_id) {
+                    msg::reply(
+                        SessionEvent::RateLimited {
+                            user: user_id,
+                            error,
+                        },
+                        0,
+                    )
+                    .expect("Failed to reply");
+                    return;
+                }
+                let Some(wordle) = session.backends.get(&backend).copied() else {
+                    msg::reply(
+                        SessionEvent::UnknownBackend {
+                            user: user_id,
+                            backend,
+                        },
+                        0,
+                    )
+                    .expect("Failed to reply");
+                    return;
+                };
+                //在发送消息之前预留, gas不够时游戏不会开始
+                let reservation = Reservation::reserve(RESERVATION_AMOUNT, RESERVATION_DURATION)
+                    .expect("Not enough gas to reserve for the game");
+                let send_msg_id = msg::send(
+                    wordle,
+                    wordle_io::Action::StartGame {
+                        user: user_id,
+                        language,
+                    },
+                    0,
+                )
+                .expect("Failed to send");
+                let origin_id = msg::id();
+                session.await_reply(
+                    user_id,
+                    wordle,
+                    send_msg_id,
+                    GameStatus::StartGameMessageSend {
+                        origin_id,
+                        backend,
+                        reservation,
+                    },
+                );
+                debug!("origin_id is:{:?}", origin_id);
+                wait_for_wordle();
+            }
+
+            Action::CheckWord {
+                user: _,
+                game_id,
+                word,
+            } => {
+                if !session.has_game(user_id, game_id) {
+                    debug!("player has no game {:?}", game_id);
+                    msg::reply(
+                        SessionEvent::UnknownGame {
+                            user: user_id,
+                            game_id,
+                        },
+                        0,
+                    )
+                    .expect("Failed to reply");
+                    return;
+                }
+                if session.guesses_paused {
+                    msg::reply(SessionEvent::Paused { user: user_id }, 0).expect("Failed to reply");
+                    return;
+                }
+                if let Err(error) = session.check_guess_rate(user_id) {
+                    msg::reply(
+                        SessionEvent::RateLimited {
+                            user: user_id,
+                            error,
+                        },
+                        0,
+                    )
+                    .expect("Failed to reply");
+                    return;
+                }
+                //统一转成小写后再检查和转发
+                let word = word.to_lowercase();
+                let config = session
+                    .game_configs
+                    .get(&game_id)
+                    .expect("Failed to get game config");
+                let validation = validate_guess(&word, config)
+                    .and_then(|()| session.check_hard_mode(game_id, &word));
+                if let Err(error) = validation {
+                    debug!("invalid guess {:?}: {:?}", word, error);
+                    msg::reply(
+                        SessionEvent::InvalidGuess {
+                            user: user_id,
+                            game_id,
+                            error,
+                        },
+                        0,
+                    )
+                    .expect("Failed to reply");
+                    return;
+                }
+                let last_attempt = session.attempts(game_id) + 1 >= session.max_play_times;
+                let previous_guess_block = session
+                    .last_guess_blocks
+                    .insert(user_id, exec::block_height());
+                //反馈在收到wordle的回复后填写
+                let guesses = session
+                    .player_times
+                    .entry(game_id)
+                    .or_insert_with(Vec::<GuessRecord>::new);
+                guesses.push(GuessRecord {
+                    word: word.clone(),
+                    correct_positions: vec![],
+                    contained_in_word: vec![],
+                });
+                debug!(
+                    "check world session.player_times is:{:?}",
+                    session.player_times
+                );
+                debug!("user_action is:{:?}", user_action);
+                let config = &session.game_configs[&game_id];
+                let wordle = session.backends[&config.backend];
+                debug!("wordle is:{:?}", wordle);
+                let send_msg_id = msg::send(
+                    wordle,
+                    wordle_io::Action::CheckWord {
+                        user: user_id,
+                        game_id: config.wordle_game_id,
+                        word,
+                        last_attempt,
+                    },
+                    0,
+                )
+                .expect("Failed to send");
+                debug!("start check word send_msg_id is:{:?}", send_msg_id);
+                let origin_id = msg::id();
+                session.await_reply(
+                    user_id,
+                    wordle,
+                    send_msg_id,
+                    GameStatus::CheckWordMessageSend {
+                        origin_id,
+                        game_id,
+                        previous_guess_block,
+                    },
+                );
+                wait_for_wordle();
+            }
+            Action::RequestHint { user: _, game_id } => {
+                if !session.has_game(user_id, game_id) {
+                    debug!("player has no game {:?}", game_id);
+                    msg::reply(
+                        SessionEvent::UnknownGame {
+                            user: user_id,
+                            game_id,
+                        },
+                        0,
+                    )
+                    .expect("Failed to reply");
+                    return;
+                }
+                if session.guesses_paused {
+                    msg::reply(SessionEvent::Paused { user: user_id }, 0).expect("Failed to reply");
+                    return;
+                }
+                //提示也算一次猜测, 同样限制频率
+                if let Err(error) = session.check_guess_rate(user_id) {
+                    msg::reply(
+                        SessionEvent::RateLimited {
+                            user: user_id,
+                            error,
+                        },
+                        0,
+                    )
+                    .expect("Failed to reply");
+                    return;
+                }
+                //最后一次机会只能用来猜测
+                if session.attempts(game_id) + 1 >= session.max_play_times {
+                    msg::reply(
+                        SessionEvent::NoHintAvailable {
+                            user: user_id,
+                            game_id,
+                        },
+                        0,
+                    )
+                    .expect("Failed to reply");
+                    return;
+                }
+                let previous_guess_block = session
+                    .last_guess_blocks
+                    .insert(user_id, exec::block_height());
+                let config = &session.game_configs[&game_id];
+                let wordle = session.backends[&config.backend];
+                let send_msg_id = msg::send(
+                    wordle,
+                    wordle_io::Action::RevealHint {
+                        user: user_id,
+                        game_id: config.wordle_game_id,
+                    },
+                    0,
+                )
+                .expect("Failed to send");
+                session.await_reply(
+                    user_id,
+                    wordle,
+                    send_msg_id,
+                    GameStatus::HintMessageSend {
+                        origin_id: msg::id(),
+                        game_id,
+                        previous_guess_block,
+                    },
+                );
+                wait_for_wordle();
+            }
+            _ => unreachable!("Owner actions are handled before the player status"),
+        }
+    } else {
+        let msg_status = player_game_status.expect("player status is empty").clone();
+        debug!("received msg_status is:{:?}", msg_status);
+        //上一条消息还没有处理完, 不排队也不panic
+        if msg_status.is_busy_for(msg_id) {
+            msg::reply(SessionEvent::Busy { user: user_id }, 0).expect("Failed to reply");
+            return;
+        }
+        match msg_status {
+            GameStatus::StartGameMessageReceived {
+                event,
+                backend,
+                reservation,
+                ..
+            } => {
+                // 获取用户id
+                let game_status = player_game_status.expect("Failed to get status");
+                debug!("received game_status is:{:?}", game_status);
+                let Event::GameStarted {
+                    game_id: wordle_game_id,
+                    language,
+                    word_length,
+                    ..
+                } = event
+                else {
+                    panic!("Invalid event");
+                };
+                //不同wordle程序的id会重复, 所以由session分配游戏id
+                let game_id = session.next_game_id;
+                session.next_game_id += 1;
+                session.game_configs.insert(
+                    game_id,
+                    GameConfig {
+                        language,
+                        word_length,
+                        backend,
+                        wordle_game_id,
+                    },
+                );
+                session.reservations.insert(game_id, reservation);
+                session.count_game(user_id);
+                session.player_game_status.remove(&user_id);
+                session
+                    .player_games
+                    .entry(user_id)
+                    .or_default()
+                    .push(game_id);
+                let game_start_event = SessionEvent::GameStarted {
+                    user: user_id,
+                    game_id,
+                };
+                msg::reply(game_start_event, 0).expect("Failed to reply");
+            }
+            GameStatus::CheckWordMessageReceived { event, game_id, .. } => {
+                debug!("received check word message id is:{:?}", msg_id);
+                // 获取用户id
+                debug!("received checked user id is :{:?}", user_id);
+                let game_status = player_game_status.expect("Failed to get status").clone();
+                debug!("check word game_status is:{:?}", game_status);
+                debug!("check word event is:{:?}", event);
+                //清空用户的状态
+                session.player_game_status.remove(&user_id);
+                //检查用户是否结束了游戏
+                match event.clone() {
+                    Event::WordChecked {
+                        user,
+                        correct_positions,
+                        contained_in_word,
+                        secret,
+                        ..
+                    } => {
+                        debug!("session.player_times.get(&game_id).expect(\"Failed to get times\").len() is :{:?}",session.player_times.get(&game_id));
+                        let guess = session
+                            .player_times
+                            .get_mut(&game_id)
+                            .and_then(|guesses| guesses.last_mut())
+                            .expect("Failed to get times");
+                        guess.correct_positions = correct_positions.clone();
+                        guess.contained_in_word = contained_in_word.clone();
+                        //wordle公开了单词说明游戏结束
+                        if let Some(secret) = secret {
+                            let feedback = Feedback {
+                                correct_positions: correct_positions.clone(),
+                                contained_in_word: contained_in_word.clone(),
+                            };
+                            let win = feedback.is_solved(secret.chars().count());
+                            let game_over_event = if win {
+                                SessionEvent::UserWin { user, game_id }
+                            } else {
+                                SessionEvent::UserLose {
+                                    user,
+                                    game_id,
+                                    word: secret.clone(),
+                                }
+                            };
+                            //先回复再结束游戏, 结束游戏会取消预留
+                            session.reply_to_player(game_id, game_over_event);
+                            session.finish_game(user_id, game_id, secret, win);
+                            return;
+                        }
+                        let mut cp = "".to_string();
+                        let mut ciw = "".to_string();
+                        for c in correct_positions {
+                            cp = cp + &c.to_string() + &",";
+                        }
+                        for c in contained_in_word {
+                            ciw = ciw + &c.to_string() + &",";
+                        }
+                        let check_word_event: SessionEvent = WordChecked {
+                            user: user_id,
+                            game_id,
+                            correct_positions: cp,
+                            contained_in_word: ciw,
+                        };
+                        session.reply_to_player(game_id, check_word_event);
+                    }
+                    //不在允许的单词列表里, 不算一次猜测
+                    Event::WordNotAllowed { .. } => {
+                        session
+                            .player_times
+                            .get_mut(&game_id)
+                            .and_then(|guesses| guesses.pop())
+                            .expect("Failed to get times");
+                        session.reply_to_player(
+                            game_id,
+                            SessionEvent::InvalidGuess {
+                                user: user_id,
+                                game_id,
+                                error: GuessError::NotInWordList,
+                            },
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            GameStatus::HintMessageReceived { event, game_id, .. } => {
+                session.player_game_status.remove(&user_id);
+                let Event::HintRevealed { hint, .. } = event else {
+                    panic!("Invalid event");
+                };
+                let hint_event = match hint {
+                    Some(hint) => {
+                        session
+                            .game_hints
+                            .entry(game_id)
+                            .or_default()
+                            .push(hint.clone());
+                        SessionEvent::HintRevealed {
+                            user: user_id,
+                            game_id,
+                            hint,
+                        }
+                    }
+                    None => SessionEvent::NoHintAvailable {
+                        user: user_id,
+                        game_id,
+                    },
+                };
+                session.reply_to_player(game_id, hint_event);
+            }
+            GameStatus::Rejected { event, .. } => {
+                session.player_game_status.remove(&user_id);
+                msg::reply(event, 0).expect("Failed to reply");
+            }
+            _ => {
+                panic!("Invalid status");
+            }
+        }
+    }
+}
+
+#[no_mangle]
+extern fn handle_reply() {
+    let session = unsafe { SESSION.as_mut().expect("State isn't initialized") };
+    let reply_to = msg::reply_to().expect("Failed to get reply_to");
+    let source = msg::source();
+    debug!("handle reply_to is:{:?}", reply_to);
+    //只按消息id对应回复, 迟到或者重复的回复没有对应的等待
+    let Some(pending) = session.pending_replies.get(&reply_to).copied() else {
+        session.drop_reply(reply_to);
+        return;
+    };
+    //只接受消息发往的wordle程序的回复, 其他程序的回复不影响等待
+    if source != pending.wordle {
+        session.drop_reply(reply_to);
+        return;
+    }
+    session.pending_replies.remove(&reply_to);
+    let game_status = session
+        .player_game_status
+        .get(&pending.player)
+        .cloned()
+        .expect("Failed to get status");
+    debug!("reply game_status is:{:?}", game_status);
+    let reply_code = msg::reply_code().expect("Failed to get the reply code");
+    let reply = if reply_code.is_error() {
+        None
+    } else {
+        msg::load::<Event>().ok()
+    };
+    let received_status = match (game_status, reply) {
+        (
+            GameStatus::StartGameMessageSend {
+                backend,
+                reservation,
+                ..
+            },
+            Some(event @ Event::GameStarted { .. }),
+        ) => GameStatus::StartGameMessageReceived {
+            origin_id: pending.origin_id,
+            event,
+            backend,
+            reservation,
+        },
+        (
+            GameStatus::CheckWordMessageSend { game_id, .. },
+            Some(event @ (Event::WordChecked { .. } | Event::WordNotAllowed { .. })),
+        ) => GameStatus::CheckWordMessageReceived {
+            origin_id: pending.origin_id,
+            event,
+            game_id,
+        },
+        (GameStatus::HintMessageSend { game_id, .. }, Some(event @ Event::HintRevealed { .. })) => {
+            GameStatus::HintMessageReceived {
+                origin_id: pending.origin_id,
+                event,
+                game_id,
+            }
+        }
+        //wordle执行出错或者回复的内容不对, 它的状态没有变, 回滚后告诉用户
+        (game_status, reply) => {
+            debug!("wordle failed: {:?} {:?}", reply_code, reply);
+            let backend = session.roll_back(pending.player, game_status);
+            GameStatus::Rejected {
+                origin_id: pending.origin_id,
+                event: SessionEvent::BackendFailed {
+                    user: pending.player,
+                    backend,
+                },
+            }
+        }
+    };
+    session
+        .player_game_status
+        .insert(pending.player, received_status);
+    if let Some(origin_id) = pending.origin_id {
+        exec::wake(origin_id).expect("Failed to wake");
+    }
+}
+
+#[no_mangle]
+extern fn handle_signal() {
+    let session = unsafe { SESSION.as_mut().expect("State isn't initialized") };
+    let failed_id = msg::signal_from().expect("Failed to get the failed message");
+    debug!("signal from {:?}: {:?}", failed_id, msg::signal_code());
+    //已经收到wordle回复的状态不回滚, 用户的下一条消息会收到结果
+    if let Some(status) = session
+        .player_game_status
+        .values_mut()
+        .find(|status| status.woken_message() == Some(failed_id))
+    {
+        status.orphan();
+        return;
+    }
+    //发给wordle的消息已经发出, wordle照样会执行, 所以等待的状态也不回滚.
+    //wordle总会回复, 出错或者超时也有回复, 到时交给用户的下一条消息
+    let Some(pending) = session
+        .pending_replies
+        .values_mut()
+        .find(|pending| pending.origin_id == Some(failed_id))
+    else {
+        return;
+    };
+    pending.origin_id = None;
+    let user = pending.player;
+    let signal = match session.player_game_status.get(&user) {
+        Some(GameStatus::StartGameMessageSend { .. }) => SessionSignal::StartGamePending { user },
+        Some(GameStatus::CheckWordMessageSend { game_id, .. }) => SessionSignal::GuessPending {
+            user,
+            game_id: *game_id,
+        },
+        Some(GameStatus::HintMessageSend { game_id, .. }) => SessionSignal::HintPending {
+            user,
+            game_id: *game_id,
+        },
+        _ => unreachable!("Only waiting messages have pending replies"),
+    };
+    msg::send(user, signal, 0).expect("Failed to send the signal");
+}
+
+#[no_mangle]
+extern fn state() {
+    let session = unsafe { SESSION.as_ref().expect("State isn't initialized") };
+    let query: StateQuery = msg::load().expect("Unable to decode StateQuery");
+    let reply = match query {
+        StateQuery::PlayerGames(player) => StateQueryReply::PlayerGames(
+            session
+                .player_games
+                .get(&player)
+                .cloned()
+                .unwrap_or_default(),
+        ),
+        StateQuery::Config => StateQueryReply::Config(session.config()),
+        StateQuery::Reservations => StateQueryReply::Reservations(session.reservations()),
+        StateQuery::Game { player, game_id } => {
+            StateQueryReply::Game(session.find_record(player, game_id).cloned())
+        }
+        StateQuery::History {
+            player,
+            offset,
+            limit,
+        } => StateQueryReply::History(
+            session
+                .player_history
+                .get(&player)
+                .map(|history| {
+                    history
+                        .iter()
+                        .rev()
+                        .skip(offset as usize)
+                        .take(limit as usize)
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ),
+        StateQuery::ShareGrid { player, game_id } => StateQueryReply::ShareGrid(
+            session
+                .find_record(player, game_id)
+                .map(GameRecord::share_grid),
+        ),
+    };
+    msg::reply(reply, 0).expect("Failed to share state");
+}
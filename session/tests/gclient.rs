@@ -0,0 +1,173 @@
+use gclient::{EventListener, EventProcessor, GearApi, Result};
+use gstd::{ActorId, Decode, Encode};
+use session_io::{
+    Action, GameOutcome, GameSessionInit, Language, SessionEvent, StateQuery, StateQueryReply,
+};
+use std::path::Path;
+use test_support::{SESSION_WASM_BINARY, WORDLE_WASM_BINARY};
+
+/// Where `cargo xtask node` puts the node binary.
+const NODE_PATH: &str = "../target/tmp/gear";
+
+/// Spawns a dev node, or returns [`None`] if its binary wasn't downloaded.
+async fn spawn_node() -> Result<Option<GearApi>> {
+    if !Path::new(NODE_PATH).exists() {
+        println!("Skipped: there is no node at {NODE_PATH}, run `cargo xtask node`");
+        return Ok(None);
+    }
+    Ok(Some(GearApi::dev_from_path(NODE_PATH).await?))
+}
+
+/// Uploads `code` and waits for its initialization, returning the address of the program.
+async fn upload(
+    api: &GearApi,
+    listener: &mut EventListener,
+    code: &[u8],
+    payload: Vec<u8>,
+) -> Result<[u8; 32]> {
+    let gas_info = api
+        .calculate_upload_gas(None, code.to_vec(), payload.clone(), 0, true)
+        .await?;
+    let (message_id, program_id, _) = api
+        .upload_program_bytes(
+            code,
+            gclient::now_micros().to_le_bytes(),
+            payload,
+            gas_info.min_limit,
+            0,
+        )
+        .await?;
+    assert!(listener.message_processed(message_id).await?.succeed());
+    Ok(program_id.as_ref().try_into().expect("Invalid program id"))
+}
+
+/// Sends `action` to `program` and decodes its reply.
+async fn send<E: Decode>(
+    api: &GearApi,
+    listener: &mut EventListener,
+    program: [u8; 32],
+    action: impl Encode,
+) -> Result<E> {
+    let payload = action.encode();
+    let gas_info = api
+        .calculate_handle_gas(None, program.into(), payload.clone(), 0, true)
+        .await?;
+    //session等待wordle回复后才继续, 预估的gas不包括这部分
+    let (message_id, _) = api
+        .send_message_bytes(program.into(), payload, gas_info.min_limit * 2, 0)
+        .await?;
+    let (_, reply, _) = listener.reply_bytes_on(message_id).await?;
+    let reply = reply.expect("The program failed");
+    Ok(E::decode(&mut reply.as_slice()).expect("Failed to decode the reply"))
+}
+
+#[tokio::test]
+async fn gclient_play_game() -> Result<()> {
+    let Some(api) = spawn_node().await? else {
+        return Ok(());
+    };
+    let mut listener = api.subscribe().await?;
+    let user = ActorId::new(api.account_id().clone().into());
+
+    let wordle = upload(&api, &mut listener, WORDLE_WASM_BINARY, vec![]).await?;
+    let init = GameSessionInit {
+        wordle_address: ActorId::new(wordle),
+        max_play_times: 3,
+    };
+    let session = upload(&api, &mut listener, SESSION_WASM_BINARY, init.encode()).await?;
+    let added: wordle_io::Event = send(
+        &api,
+        &mut listener,
+        wordle,
+        wordle_io::Action::AddSession(ActorId::new(session)),
+    )
+    .await?;
+    assert!(matches!(added, wordle_io::Event::SessionAdded(_)));
+
+    let fixed: wordle_io::Event = send(
+        &api,
+        &mut listener,
+        wordle,
+        wordle_io::Action::FixSecret {
+            user,
+            word: Some("horse".to_string()),
+        },
+    )
+    .await?;
+    assert!(matches!(fixed, wordle_io::Event::SecretFixed { .. }));
+
+    let started: SessionEvent = send(
+        &api,
+        &mut listener,
+        session,
+        Action::StartGame {
+            user,
+            language: Language::En,
+            backend: 0,
+        },
+    )
+    .await?;
+    let SessionEvent::GameStarted { game_id, .. } = started else {
+        panic!("Failed to start a game: {started:?}");
+    };
+
+    let checked: SessionEvent = send(
+        &api,
+        &mut listener,
+        session,
+        Action::CheckWord {
+            user,
+            game_id,
+            word: "house".to_string(),
+        },
+    )
+    .await?;
+    assert!(
+        matches!(
+            &checked,
+            SessionEvent::WordChecked { correct_positions, contained_in_word, .. }
+                if correct_positions == "0,1,3,4," && contained_in_word.is_empty()
+        ),
+        "{checked:?}"
+    );
+
+    let open_games: StateQueryReply = api
+        .read_state(session.into(), StateQuery::PlayerGames(user).encode())
+        .await?;
+    assert_eq!(open_games, StateQueryReply::PlayerGames(vec![game_id]));
+
+    let won: SessionEvent = send(
+        &api,
+        &mut listener,
+        session,
+        Action::CheckWord {
+            user,
+            game_id,
+            word: "horse".to_string(),
+        },
+    )
+    .await?;
+    assert!(matches!(won, SessionEvent::UserWin { .. }), "{won:?}");
+
+    let open_games: StateQueryReply = api
+        .read_state(session.into(), StateQuery::PlayerGames(user).encode())
+        .await?;
+    assert_eq!(open_games, StateQueryReply::PlayerGames(vec![]));
+    let record: StateQueryReply = api
+        .read_state(
+            session.into(),
+            StateQuery::Game {
+                player: user,
+                game_id,
+            }
+            .encode(),
+        )
+        .await?;
+    let StateQueryReply::Game(Some(record)) = record else {
+        panic!("The finished game isn't in the history: {record:?}");
+    };
+    assert_eq!(record.secret, "horse");
+    assert_eq!(record.outcome, GameOutcome::Win);
+    assert_eq!(record.guesses.len(), 2);
+    Ok(())
+}
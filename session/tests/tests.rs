@@ -1,43 +1,140 @@
-use gtest::{Log, Program, ProgramBuilder, System};
-use session_io::Action::{CheckWord, StartGame};
-use session_io::{GameSessionInit, SessionEvent};
-use wordle_io::Event;
+use gstd::{Decode, Encode};
+use gtest::{Log, Program, RunResult, System, WasmProgram};
+use session_io::Action::{
+    AddBackend, CheckWord, ExportState, ForceEndGame, ImportState, RequestHint, SetBackendAddress,
+    SetHardMode, SetMaxPlayTimes, SetPaused, SetRateLimits, StartGame, TopUpReservation,
+    TransferOwnership,
+};
+use session_io::{
+    GameOutcome, GameRecord, GameSessionInit, GuessError, GuessRecord, HardModeError, Hint,
+    Language, RateLimitError, RateLimits, SessionConfig, SessionEvent, SessionSignal, StateChunk,
+    StateQuery, StateQueryReply,
+};
+use test_support::{GameFixture, SESSION_WASM_BINARY, WORDLE_PROGRAM_ID, WORDLE_WASM_BINARY};
 
-const GAME_SESSION_PROGRAM_ID: u64 = 1;
-const GAME_WORDLE_PROGRAM_ID: u64 = 2;
 const USER: u64 = 20;
+const OTHER_USER: u64 = 21;
+const MOCK_WORDLE_PROGRAM_ID: u64 = 3;
+
+/// A wordle program answering every action with `reply`, or never answering if it's [`None`].
+#[derive(Debug)]
+struct MockWordle {
+    reply: Option<wordle_io::Event>,
+}
+
+impl WasmProgram for MockWordle {
+    fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(self.reply.as_ref().map(Encode::encode))
+    }
+
+    fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+        Ok(vec![])
+    }
+}
+
+/// A wordle program failing every action.
+#[derive(Debug)]
+struct FailingWordle;
+
+impl WasmProgram for FailingWordle {
+    fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Err("wordle failed")
+    }
+
+    fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+        Ok(vec![])
+    }
+}
+
+/// Registers a [`MockWordle`] as backend 1 of the session.
+fn add_mock_wordle<'a>(
+    system: &'a System,
+    session_program: &Program,
+    reply: Option<wordle_io::Event>,
+) -> Program<'a> {
+    add_mock_backend(system, session_program, MockWordle { reply })
+}
+
+/// Registers `mock` as backend 1 of the session.
+fn add_mock_backend<'a>(
+    system: &'a System,
+    session_program: &Program,
+    mock: impl WasmProgram + 'static,
+) -> Program<'a> {
+    let mock_program = Program::mock_with_id(system, MOCK_WORDLE_PROGRAM_ID, mock);
+    let mock_init_result = mock_program.send_bytes(USER, []);
+    assert!(!mock_init_result.main_failed(), "mock init failed");
+    let add_result = session_program.send(USER, AddBackend(MOCK_WORDLE_PROGRAM_ID.into()));
+    assert!(
+        add_result.contains(&Log::builder().payload(SessionEvent::BackendAdded {
+            backend: 1,
+            address: MOCK_WORDLE_PROGRAM_ID.into(),
+        })),
+        "add backend failed"
+    );
+    mock_program
+}
 
 fn init_game(system: &System) -> (Program, Program) {
-    let session_program =
-        ProgramBuilder::from_file("../target/wasm32-unknown-unknown/debug/session.opt.wasm")
-            .with_id(GAME_SESSION_PROGRAM_ID)
-            .with_meta_file("../target/wasm32-unknown-unknown/debug/session.meta.txt")
-            .build(&system);
-    let wordle_program =
-        ProgramBuilder::from_file("../target/wasm32-unknown-unknown/debug/wordle.opt.wasm")
-            .with_id(GAME_WORDLE_PROGRAM_ID)
-            .with_meta_file("../target/wasm32-unknown-unknown/debug/wordle.meta.txt")
-            .build(&system);
-
-    let wordle_init_result = wordle_program.send::<u64, [u8; 0]>(USER, []);
-    assert!(!wordle_init_result.main_failed(), "wordle init failed");
+    test_support::deploy(system, USER, 3)
+}
 
-    let session_init_result = session_program.send(
+/// Makes the games `user` starts on the wordle program of [`init_game`] use `word`.
+fn fix_secret(wordle_program: &Program, user: u64, word: &str) {
+    let fix_result = wordle_program.send(
         USER,
-        GameSessionInit {
-            wordle_address: GAME_WORDLE_PROGRAM_ID.into(),
-            max_play_times: 3,
+        wordle_io::Action::FixSecret {
+            user: user.into(),
+            word: Some(word.to_string()),
         },
     );
-    assert!(!session_init_result.main_failed(), "session init success");
-    (session_program, wordle_program)
+    assert!(!fix_result.main_failed(), "fix secret failed");
 }
 #[test]
 pub fn test_init() {
     let system = System::new();
     system.init_logger();
 
-    let (session_program, wordle_program) = init_game(&system);
+    let (session_program, _wordle_program) = init_game(&system);
+    let config: StateQueryReply = session_program
+        .read_state(StateQuery::Config)
+        .expect("Failed to read state");
+    assert_eq!(
+        config,
+        StateQueryReply::Config(SessionConfig {
+            owner: USER.into(),
+            backends: vec![(0, WORDLE_PROGRAM_ID.into())],
+            max_play_times: 3,
+            new_games_paused: false,
+            guesses_paused: false,
+            rate_limits: RateLimits::default(),
+            hard_mode: false,
+        })
+    );
 }
 #[test]
 pub fn test_play_success() {
@@ -45,12 +142,23 @@ pub fn test_play_success() {
     system.init_logger();
 
     let (session_program, wordle_program) = init_game(&system);
-    let start_result = session_program.send(USER, StartGame { user: USER.into() });
+    fix_secret(&wordle_program, USER, "horse");
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
     assert!(!start_result.main_failed(), "start run failed");
     let start_logs = start_result.log();
     println!("start logs is:{:?}", start_logs);
     assert!(
-        start_result.contains(&Log::builder().payload(Event::GameStarted { user: USER.into() })),
+        start_result.contains(&Log::builder().payload(SessionEvent::GameStarted {
+            user: USER.into(),
+            game_id: 0,
+        })),
         "receive log error!"
     );
 
@@ -59,6 +167,7 @@ pub fn test_play_success() {
         USER,
         CheckWord {
             user: USER.into(),
+            game_id: 0,
             word: "house".to_string(),
         },
     );
@@ -66,6 +175,7 @@ pub fn test_play_success() {
     assert!(
         wordle_result.contains(&Log::builder().payload(SessionEvent::WordChecked {
             user: USER.into(),
+            game_id: 0,
             correct_positions: "0,1,3,4,".to_string(),
             contained_in_word: "".to_string(),
         }))
@@ -75,18 +185,23 @@ pub fn test_play_success() {
         USER,
         CheckWord {
             user: USER.into(),
+            game_id: 0,
             word: "horse".to_string(),
         },
     );
 
     assert!(!success_wordle_result.main_failed(), "wordle run failed");
     assert!(
-        success_wordle_result.contains(&Log::builder().payload(SessionEvent::WordChecked {
+        success_wordle_result.contains(&Log::builder().payload(SessionEvent::UserWin {
             user: USER.into(),
-            correct_positions: "0,1,2,3,4,".to_string(),
-            contained_in_word: "".to_string(),
+            game_id: 0,
         }))
     );
+
+    let open_games: StateQueryReply = session_program
+        .read_state(StateQuery::PlayerGames(USER.into()))
+        .expect("Failed to read state");
+    assert_eq!(open_games, StateQueryReply::PlayerGames(vec![]));
 }
 
 #[test]
@@ -94,14 +209,1874 @@ pub fn test_play_fail() {
     let system = System::new();
     system.init_logger();
 
+    let (session_program, _wordle_program) = init_game(&system);
+
+    let wordle_result = session_program.send(
+        USER,
+        CheckWord {
+            user: USER.into(),
+            game_id: 0,
+            word: "house".to_string(),
+        },
+    );
+    //没有游戏的玩家也要收到回复, 而不是panic
+    assert!(!wordle_result.main_failed(), "wordle run failed");
+    assert!(
+        wordle_result.contains(&Log::builder().payload(SessionEvent::UnknownGame {
+            user: USER.into(),
+            game_id: 0,
+        })),
+        "{wordle_result:?}"
+    );
+
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+    //别人的游戏和不存在的游戏一样被拒绝
+    let other_result = session_program.send(
+        OTHER_USER,
+        CheckWord {
+            user: OTHER_USER.into(),
+            game_id: 0,
+            word: "house".to_string(),
+        },
+    );
+    assert!(
+        other_result.contains(&Log::builder().payload(SessionEvent::UnknownGame {
+            user: OTHER_USER.into(),
+            game_id: 0,
+        })),
+        "{other_result:?}"
+    );
+    let hint_result = session_program.send(
+        USER,
+        RequestHint {
+            user: USER.into(),
+            game_id: 1,
+        },
+    );
+    assert!(
+        hint_result.contains(&Log::builder().payload(SessionEvent::UnknownGame {
+            user: USER.into(),
+            game_id: 1,
+        })),
+        "{hint_result:?}"
+    );
+}
+
+#[test]
+pub fn test_play_concurrent_games() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, wordle_program) = init_game(&system);
+    fix_secret(&wordle_program, USER, "horse");
+    let first_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!first_result.main_failed(), "first start run failed");
+    let second_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!second_result.main_failed(), "second start run failed");
+    assert!(
+        second_result.contains(&Log::builder().payload(SessionEvent::GameStarted {
+            user: USER.into(),
+            game_id: 1,
+        })),
+        "second game should get its own id"
+    );
+
+    let open_games: StateQueryReply = session_program
+        .read_state(StateQuery::PlayerGames(USER.into()))
+        .expect("Failed to read state");
+    assert_eq!(open_games, StateQueryReply::PlayerGames(vec![0, 1]));
+
+    //the first game is still playable after the second one started
+    let wordle_result = session_program.send(
+        USER,
+        CheckWord {
+            user: USER.into(),
+            game_id: 0,
+            word: "house".to_string(),
+        },
+    );
+    assert!(!wordle_result.main_failed(), "wordle run failed");
+    assert!(
+        wordle_result.contains(&Log::builder().payload(SessionEvent::WordChecked {
+            user: USER.into(),
+            game_id: 0,
+            correct_positions: "0,1,3,4,".to_string(),
+            contained_in_word: "".to_string(),
+        }))
+    );
+}
+
+#[test]
+pub fn test_game_history() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, wordle_program) = init_game(&system);
+    fix_secret(&wordle_program, USER, "horse");
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+    for word in ["house", "horse"] {
+        let wordle_result = session_program.send(
+            USER,
+            CheckWord {
+                user: USER.into(),
+                game_id: 0,
+                word: word.to_string(),
+            },
+        );
+        assert!(!wordle_result.main_failed(), "wordle run failed");
+    }
+
+    let mut expected = GameRecord {
+        game_id: 0,
+        secret: "horse".to_string(),
+        guesses: vec![
+            GuessRecord {
+                word: "house".to_string(),
+                correct_positions: vec![0, 1, 3, 4],
+                contained_in_word: vec![],
+            },
+            GuessRecord {
+                word: "horse".to_string(),
+                correct_positions: vec![0, 1, 2, 3, 4],
+                contained_in_word: vec![],
+            },
+        ],
+        hints: vec![],
+        outcome: GameOutcome::Win,
+        block_number: 0,
+    };
+    let game: StateQueryReply = session_program
+        .read_state(StateQuery::Game {
+            player: USER.into(),
+            game_id: 0,
+        })
+        .expect("Failed to read state");
+    let StateQueryReply::Game(Some(record)) = game else {
+        panic!("game 0 should be in the history");
+    };
+    assert!(record.block_number > 0 && record.block_number <= system.block_height());
+    expected.block_number = record.block_number;
+    assert_eq!(record, expected);
+
+    let history: StateQueryReply = session_program
+        .read_state(StateQuery::History {
+            player: USER.into(),
+            offset: 0,
+            limit: 10,
+        })
+        .expect("Failed to read state");
+    assert_eq!(history, StateQueryReply::History(vec![expected]));
+}
+
+#[test]
+pub fn test_share_grid() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, wordle_program) = init_game(&system);
+    fix_secret(&wordle_program, USER, "horse");
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+    for word in ["house", "horse"] {
+        let wordle_result = session_program.send(
+            USER,
+            CheckWord {
+                user: USER.into(),
+                game_id: 0,
+                word: word.to_string(),
+            },
+        );
+        assert!(!wordle_result.main_failed(), "wordle run failed");
+    }
+
+    let grid: StateQueryReply = session_program
+        .read_state(StateQuery::ShareGrid {
+            player: USER.into(),
+            game_id: 0,
+        })
+        .expect("Failed to read state");
+    assert_eq!(
+        grid,
+        StateQueryReply::ShareGrid(Some("🟩🟩⬛🟩🟩\n🟩🟩🟩🟩🟩".to_string()))
+    );
+}
+
+#[test]
+pub fn test_play_unicode_word() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, wordle_program) = init_game(&system);
+    fix_secret(&wordle_program, USER, "книга");
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::Ru,
+            backend: 0,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+
+    //五个西里尔字母占十个字节, 长度检查和位置都要按字符计算
+    let wordle_result = session_program.send(
+        USER,
+        CheckWord {
+            user: USER.into(),
+            game_id: 0,
+            word: "кошка".to_string(),
+        },
+    );
+    assert!(
+        wordle_result.contains(&Log::builder().payload(SessionEvent::WordChecked {
+            user: USER.into(),
+            game_id: 0,
+            correct_positions: "0,4,".to_string(),
+            contained_in_word: "3,".to_string(),
+        })),
+        "{wordle_result:?}"
+    );
+    let wordle_result = session_program.send(
+        USER,
+        CheckWord {
+            user: USER.into(),
+            game_id: 0,
+            word: "книга".to_string(),
+        },
+    );
+    assert!(
+        wordle_result.contains(&Log::builder().payload(SessionEvent::UserWin {
+            user: USER.into(),
+            game_id: 0,
+        })),
+        "{wordle_result:?}"
+    );
+}
+
+#[test]
+pub fn test_play_normalized_guess() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, wordle_program) = init_game(&system);
+    fix_secret(&wordle_program, USER, "horse");
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+
+    //非法字符不消耗猜测次数
+    for _ in 0..3 {
+        let invalid_result = session_program.send(
+            USER,
+            CheckWord {
+                user: USER.into(),
+                game_id: 0,
+                word: "hou5e".to_string(),
+            },
+        );
+        assert!(!invalid_result.main_failed(), "invalid guess run failed");
+        assert!(
+            invalid_result.contains(&Log::builder().payload(SessionEvent::InvalidGuess {
+                user: USER.into(),
+                game_id: 0,
+                error: GuessError::InvalidCharacter { position: 3 },
+            }))
+        );
+    }
+
+    let wordle_result = session_program.send(
+        USER,
+        CheckWord {
+            user: USER.into(),
+            game_id: 0,
+            word: "HOUSE".to_string(),
+        },
+    );
+    assert!(!wordle_result.main_failed(), "wordle run failed");
+    assert!(
+        wordle_result.contains(&Log::builder().payload(SessionEvent::WordChecked {
+            user: USER.into(),
+            game_id: 0,
+            correct_positions: "0,1,3,4,".to_string(),
+            contained_in_word: "".to_string(),
+        }))
+    );
+}
+
+#[test]
+pub fn test_play_wrong_length() {
+    let system = System::new();
+    system.init_logger();
+
     let (session_program, wordle_program) = init_game(&system);
+    fix_secret(&wordle_program, USER, "horse");
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+
+    //长度不对的猜测不消耗猜测次数
+    for (word, actual) in [("hous", 4), ("houses", 6), ("", 0), ("housee", 6)] {
+        let invalid_result = session_program.send(
+            USER,
+            CheckWord {
+                user: USER.into(),
+                game_id: 0,
+                word: word.to_string(),
+            },
+        );
+        assert!(!invalid_result.main_failed(), "invalid guess run failed");
+        assert!(
+            invalid_result.contains(&Log::builder().payload(SessionEvent::InvalidGuess {
+                user: USER.into(),
+                game_id: 0,
+                error: GuessError::WrongLength {
+                    expected: 5,
+                    actual,
+                },
+            }))
+        );
+    }
 
     let wordle_result = session_program.send(
         USER,
         CheckWord {
             user: USER.into(),
+            game_id: 0,
             word: "house".to_string(),
         },
     );
-    assert!(wordle_result.main_failed(), "wordle run failed");
+    assert!(!wordle_result.main_failed(), "wordle run failed");
+    assert!(
+        wordle_result.contains(&Log::builder().payload(SessionEvent::WordChecked {
+            user: USER.into(),
+            game_id: 0,
+            correct_positions: "0,1,3,4,".to_string(),
+            contained_in_word: "".to_string(),
+        }))
+    );
+}
+
+fn exported_chunk(result: &RunResult) -> StateChunk {
+    result
+        .log()
+        .iter()
+        .find_map(|log| match SessionEvent::decode(&mut log.payload()) {
+            Ok(SessionEvent::StateExported(chunk)) => Some(chunk),
+            _ => None,
+        })
+        .expect("no state exported")
+}
+
+#[test]
+pub fn test_migrate_session() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, wordle_program) = init_game(&system);
+    fix_secret(&wordle_program, USER, "horse");
+    fix_secret(&wordle_program, OTHER_USER, "human");
+    for (user, words) in [(USER, vec!["house", "horse"]), (OTHER_USER, vec!["house"])] {
+        let start_result = session_program.send(
+            user,
+            StartGame {
+                user: user.into(),
+                language: Language::En,
+                backend: 0,
+            },
+        );
+        assert!(!start_result.main_failed(), "start run failed");
+        let game_id = if user == USER { 0 } else { 1 };
+        for word in words {
+            let wordle_result = session_program.send(
+                user,
+                CheckWord {
+                    user: user.into(),
+                    game_id,
+                    word: word.to_string(),
+                },
+            );
+            assert!(!wordle_result.main_failed(), "wordle run failed");
+        }
+    }
+
+    //只有owner可以导出状态
+    let forbidden_result = session_program.send(
+        OTHER_USER,
+        ExportState {
+            offset: 0,
+            limit: 1,
+        },
+    );
+    assert!(forbidden_result.main_failed(), "only the owner can export");
+
+    let new_session_program = Program::from_binary_with_id(&system, 3, SESSION_WASM_BINARY);
+    let init_result = new_session_program.send(
+        USER,
+        GameSessionInit {
+            wordle_address: WORDLE_PROGRAM_ID.into(),
+            max_play_times: 3,
+        },
+    );
+    assert!(!init_result.main_failed(), "new session init failed");
+    //新的session也要能在wordle上玩
+    let add_session_result = wordle_program.send(USER, wordle_io::Action::AddSession(3.into()));
+    assert!(
+        !add_session_result.main_failed(),
+        "adding the session failed"
+    );
+
+    //每次导出一个用户, 直到没有下一块
+    let mut offset = Some(0);
+    let mut chunks = 0;
+    let mut last_chunk = None;
+    while let Some(next) = offset {
+        let export_result = session_program.send(
+            USER,
+            ExportState {
+                offset: next,
+                limit: 1,
+            },
+        );
+        assert!(!export_result.main_failed(), "export failed");
+        let chunk = exported_chunk(&export_result);
+        offset = chunk.next;
+        last_chunk = Some(chunk.clone());
+        let import_result = new_session_program.send(USER, ImportState(chunk));
+        assert!(!import_result.main_failed(), "import failed");
+        assert!(import_result
+            .contains(&Log::builder().payload(SessionEvent::StateImported { players: 1 })));
+        chunks += 1;
+    }
+    assert_eq!(chunks, 2);
+    //重发的分块不会重复导入游戏
+    let last_chunk = last_chunk.expect("no chunk exported");
+    let import_result = new_session_program.send(USER, ImportState(last_chunk));
+    assert!(!import_result.main_failed(), "import failed");
+
+    for query in [
+        StateQuery::PlayerGames(USER.into()),
+        StateQuery::PlayerGames(OTHER_USER.into()),
+        StateQuery::History {
+            player: USER.into(),
+            offset: 0,
+            limit: 10,
+        },
+    ] {
+        let old_state: StateQueryReply = session_program
+            .read_state(query.clone())
+            .expect("Failed to read state");
+        let new_state: StateQueryReply = new_session_program
+            .read_state(query)
+            .expect("Failed to read state");
+        assert_eq!(old_state, new_state);
+    }
+
+    //导入的backend不覆盖新程序已有的
+    let backends_chunk = StateChunk {
+        version: 3,
+        backends: vec![(0, 99.into()), (1, 98.into())],
+        next_game_id: 0,
+        players: vec![],
+        next: None,
+    };
+    let import_result = new_session_program.send(USER, ImportState(backends_chunk));
+    assert!(!import_result.main_failed(), "import failed");
+    let config: StateQueryReply = new_session_program
+        .read_state(StateQuery::Config)
+        .expect("Failed to read state");
+    let StateQueryReply::Config(config) = config else {
+        panic!("unexpected state reply");
+    };
+    assert_eq!(
+        config.backends,
+        vec![(0, WORDLE_PROGRAM_ID.into()), (1, 98.into())]
+    );
+
+    //迁移后的游戏可以继续
+    let wordle_result = new_session_program.send(
+        OTHER_USER,
+        CheckWord {
+            user: OTHER_USER.into(),
+            game_id: 1,
+            word: "human".to_string(),
+        },
+    );
+    assert!(
+        wordle_result.contains(&Log::builder().payload(SessionEvent::UserWin {
+            user: OTHER_USER.into(),
+            game_id: 1,
+        }))
+    );
+}
+
+#[test]
+pub fn test_import_legacy_state() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, _wordle_program) = init_game(&system);
+    let empty_chunk = StateChunk {
+        version: 3,
+        backends: vec![],
+        next_game_id: 0,
+        players: vec![],
+        next: None,
+    };
+    let import_index = ImportState(empty_chunk).encode()[0];
+    let guess = GuessRecord {
+        word: "house".to_string(),
+        correct_positions: vec![0, 1, 3, 4],
+        contained_in_word: vec![],
+    };
+    let open_games = vec![(0u64, Language::En, 5u8, vec![guess.clone()])];
+    //比历史记录上限多10局
+    let history: Vec<_> = (10..70u64)
+        .map(|game_id| {
+            (
+                game_id,
+                "horse".to_string(),
+                vec![guess.clone()],
+                GameOutcome::Lose,
+                game_id as u32,
+            )
+        })
+        .collect();
+    let player: gstd::ActorId = USER.into();
+    //第一版的分块没有后端和提示, 字段依次是版本、玩家和下一块
+    let v1_chunk = (1u16, vec![(player, open_games, history)], None::<u32>).encode();
+    //重发同一块也只导入一次
+    for _ in 0..2 {
+        let import_result =
+            session_program.send_bytes(USER, [vec![import_index], v1_chunk.clone()].concat());
+        assert!(
+            import_result
+                .contains(&Log::builder().payload(SessionEvent::StateImported { players: 1 })),
+            "{import_result:?}"
+        );
+    }
+
+    let open_games: StateQueryReply = session_program
+        .read_state(StateQuery::PlayerGames(USER.into()))
+        .expect("Failed to read state");
+    assert_eq!(open_games, StateQueryReply::PlayerGames(vec![0]));
+    let history: StateQueryReply = session_program
+        .read_state(StateQuery::History {
+            player: USER.into(),
+            offset: 0,
+            limit: 100,
+        })
+        .expect("Failed to read state");
+    let StateQueryReply::History(history) = history else {
+        panic!("unexpected reply {history:?}");
+    };
+    //只保留最近的50局
+    let game_ids: Vec<_> = history.iter().map(|record| record.game_id).collect();
+    assert_eq!(game_ids, (20..70).rev().collect::<Vec<_>>());
+    assert!(history.iter().all(|record| record.hints.is_empty()));
+
+    //新游戏的id在导入的游戏之后
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(
+        start_result.contains(&Log::builder().payload(SessionEvent::GameStarted {
+            user: USER.into(),
+            game_id: 70,
+        })),
+        "{start_result:?}"
+    );
+}
+
+#[test]
+pub fn test_admin_actions() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, _wordle_program) = init_game(&system);
+
+    //只有owner可以暂停
+    let forbidden_result = session_program.send(
+        OTHER_USER,
+        SetPaused {
+            new_games: true,
+            guesses: true,
+        },
+    );
+    assert!(forbidden_result.main_failed(), "only the owner can pause");
+
+    let pause_result = session_program.send(
+        USER,
+        SetPaused {
+            new_games: true,
+            guesses: false,
+        },
+    );
+    let mut config = SessionConfig {
+        owner: USER.into(),
+        backends: vec![(0, WORDLE_PROGRAM_ID.into())],
+        max_play_times: 3,
+        new_games_paused: true,
+        guesses_paused: false,
+        rate_limits: RateLimits::default(),
+        hard_mode: false,
+    };
+    assert!(
+        pause_result.contains(&Log::builder().payload(SessionEvent::ConfigChanged(config.clone())))
+    );
+    let start_result = session_program.send(
+        OTHER_USER,
+        StartGame {
+            user: OTHER_USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(
+        start_result.contains(&Log::builder().payload(SessionEvent::Paused {
+            user: OTHER_USER.into()
+        }))
+    );
+
+    let pause_result = session_program.send(
+        USER,
+        SetPaused {
+            new_games: false,
+            guesses: true,
+        },
+    );
+    assert!(!pause_result.main_failed(), "pause failed");
+    let start_result = session_program.send(
+        OTHER_USER,
+        StartGame {
+            user: OTHER_USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+    let wordle_result = session_program.send(
+        OTHER_USER,
+        CheckWord {
+            user: OTHER_USER.into(),
+            game_id: 0,
+            word: "house".to_string(),
+        },
+    );
+    assert!(
+        wordle_result.contains(&Log::builder().payload(SessionEvent::Paused {
+            user: OTHER_USER.into()
+        }))
+    );
+
+    let max_play_times_result = session_program.send(USER, SetMaxPlayTimes(6));
+    config.new_games_paused = false;
+    config.guesses_paused = true;
+    config.max_play_times = 6;
+    assert!(max_play_times_result
+        .contains(&Log::builder().payload(SessionEvent::ConfigChanged(config.clone()))));
+
+    let wordle_address_result = session_program.send(
+        USER,
+        SetBackendAddress {
+            backend: 0,
+            address: 3.into(),
+        },
+    );
+    config.backends = vec![(0, 3.into())];
+    assert!(wordle_address_result
+        .contains(&Log::builder().payload(SessionEvent::ConfigChanged(config.clone()))));
+
+    let force_end_result = session_program.send(
+        USER,
+        ForceEndGame {
+            player: OTHER_USER.into(),
+            game_id: 0,
+        },
+    );
+    assert!(
+        force_end_result.contains(&Log::builder().payload(SessionEvent::GameForceEnded {
+            player: OTHER_USER.into(),
+            game_id: 0,
+        }))
+    );
+    let open_games: StateQueryReply = session_program
+        .read_state(StateQuery::PlayerGames(OTHER_USER.into()))
+        .expect("Failed to read state");
+    assert_eq!(open_games, StateQueryReply::PlayerGames(vec![]));
+
+    let transfer_result = session_program.send(
+        USER,
+        TransferOwnership {
+            new_owner: OTHER_USER.into(),
+        },
+    );
+    assert!(transfer_result.contains(&Log::builder().payload(
+        SessionEvent::OwnershipTransferred {
+            previous_owner: USER.into(),
+            new_owner: OTHER_USER.into(),
+        }
+    )));
+    let forbidden_result = session_program.send(USER, SetMaxPlayTimes(3));
+    assert!(
+        forbidden_result.main_failed(),
+        "the previous owner lost access"
+    );
+
+    config.owner = OTHER_USER.into();
+    let state: StateQueryReply = session_program
+        .read_state(StateQuery::Config)
+        .expect("Failed to read state");
+    assert_eq!(state, StateQueryReply::Config(config));
+}
+
+#[test]
+pub fn test_play_multiple_backends() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, _wordle_program) = init_game(&system);
+    let second_wordle_program = Program::from_binary_with_id(&system, 3, WORDLE_WASM_BINARY);
+    let wordle_init_result = second_wordle_program.send::<u64, [u8; 0]>(USER, []);
+    assert!(!wordle_init_result.main_failed(), "wordle init failed");
+
+    let add_result = session_program.send(USER, AddBackend(3.into()));
+    assert!(
+        add_result.contains(&Log::builder().payload(SessionEvent::BackendAdded {
+            backend: 1,
+            address: 3.into(),
+        }))
+    );
+
+    let unknown_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 2,
+        },
+    );
+    assert!(
+        unknown_result.contains(&Log::builder().payload(SessionEvent::UnknownBackend {
+            user: USER.into(),
+            backend: 2,
+        }))
+    );
+
+    //两个wordle程序都给自己的第一局游戏分配0号, session的游戏id不会重复
+    for (backend, game_id) in [(0, 0), (1, 1)] {
+        let start_result = session_program.send(
+            USER,
+            StartGame {
+                user: USER.into(),
+                language: Language::En,
+                backend,
+            },
+        );
+        assert!(
+            start_result.contains(&Log::builder().payload(SessionEvent::GameStarted {
+                user: USER.into(),
+                game_id,
+            })),
+            "start run failed"
+        );
+    }
+
+    for game_id in [1, 0] {
+        let wordle_result = session_program.send(
+            USER,
+            CheckWord {
+                user: USER.into(),
+                game_id,
+                word: "hoxxx".to_string(),
+            },
+        );
+        assert!(!wordle_result.main_failed(), "wordle run failed");
+        let checked = wordle_result.log().iter().any(|log| {
+            matches!(
+                SessionEvent::decode(&mut log.payload()),
+                Ok(SessionEvent::WordChecked { game_id: id, .. }) if id == game_id
+            )
+        });
+        assert!(checked, "the guess must be routed to the game's backend");
+    }
+}
+
+#[test]
+pub fn test_request_hint() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, wordle_program) = init_game(&system);
+    fix_secret(&wordle_program, USER, "horse");
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+    let wordle_result = session_program.send(
+        USER,
+        CheckWord {
+            user: USER.into(),
+            game_id: 0,
+            word: "house".to_string(),
+        },
+    );
+    assert!(!wordle_result.main_failed(), "wordle run failed");
+
+    //只有第2个位置还没有猜中
+    let hint = Hint {
+        position: 2,
+        letter: "r".to_string(),
+    };
+    let hint_result = session_program.send(
+        USER,
+        RequestHint {
+            user: USER.into(),
+            game_id: 0,
+        },
+    );
+    assert!(
+        hint_result.contains(&Log::builder().payload(SessionEvent::HintRevealed {
+            user: USER.into(),
+            game_id: 0,
+            hint: hint.clone(),
+        })),
+        "hint run failed"
+    );
+
+    //提示用掉了一次机会, 最后一次机会只能用来猜测
+    let hint_result = session_program.send(
+        USER,
+        RequestHint {
+            user: USER.into(),
+            game_id: 0,
+        },
+    );
+    assert!(
+        hint_result.contains(&Log::builder().payload(SessionEvent::NoHintAvailable {
+            user: USER.into(),
+            game_id: 0,
+        }))
+    );
+
+    let wordle_result = session_program.send(
+        USER,
+        CheckWord {
+            user: USER.into(),
+            game_id: 0,
+            word: "horse".to_string(),
+        },
+    );
+    assert!(
+        wordle_result.contains(&Log::builder().payload(SessionEvent::UserWin {
+            user: USER.into(),
+            game_id: 0,
+        }))
+    );
+    let game: StateQueryReply = session_program
+        .read_state(StateQuery::Game {
+            player: USER.into(),
+            game_id: 0,
+        })
+        .expect("Failed to read state");
+    let StateQueryReply::Game(Some(record)) = game else {
+        panic!("game 0 should be in the history");
+    };
+    assert_eq!(record.hints, vec![hint]);
+}
+
+#[test]
+pub fn test_gas_reservation() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, wordle_program) = init_game(&system);
+    fix_secret(&wordle_program, OTHER_USER, "human");
+    let start_result = session_program.send(
+        OTHER_USER,
+        StartGame {
+            user: OTHER_USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+
+    //开始游戏时预留了gas
+    let reservations: StateQueryReply = session_program
+        .read_state(StateQuery::Reservations)
+        .expect("Failed to read state");
+    let StateQueryReply::Reservations(reservations) = reservations else {
+        panic!("unexpected state reply");
+    };
+    assert_eq!(reservations.len(), 1);
+    assert_eq!(reservations[0].game_id, 0);
+    assert!(reservations[0].valid_until > system.block_height());
+
+    //只有owner可以补充预留
+    let forbidden_result = session_program.send(
+        OTHER_USER,
+        TopUpReservation {
+            game_id: 0,
+            amount: 2_000_000_000,
+            duration: 100,
+        },
+    );
+    assert!(forbidden_result.main_failed(), "only the owner can top up");
+
+    let top_up_result = session_program.send(
+        USER,
+        TopUpReservation {
+            game_id: 0,
+            amount: 2_000_000_000,
+            duration: 100,
+        },
+    );
+    assert!(!top_up_result.main_failed(), "top up failed");
+    let reservations: StateQueryReply = session_program
+        .read_state(StateQuery::Reservations)
+        .expect("Failed to read state");
+    let StateQueryReply::Reservations(reservations) = reservations else {
+        panic!("unexpected state reply");
+    };
+    assert_eq!(reservations.len(), 1);
+    assert_eq!(reservations[0].amount, 2_000_000_000);
+    assert!(
+        top_up_result.contains(
+            &Log::builder().payload(SessionEvent::ReservationToppedUp(reservations[0].clone()))
+        ),
+        "top up failed"
+    );
+
+    //游戏结束后取消预留
+    let wordle_result = session_program.send(
+        OTHER_USER,
+        CheckWord {
+            user: OTHER_USER.into(),
+            game_id: 0,
+            word: "horse".to_string(),
+        },
+    );
+    assert!(!wordle_result.main_failed(), "wordle run failed");
+    let wordle_result = session_program.send(
+        OTHER_USER,
+        CheckWord {
+            user: OTHER_USER.into(),
+            game_id: 0,
+            word: "house".to_string(),
+        },
+    );
+    assert!(!wordle_result.main_failed(), "wordle run failed");
+    let wordle_result = session_program.send(
+        OTHER_USER,
+        CheckWord {
+            user: OTHER_USER.into(),
+            game_id: 0,
+            word: "human".to_string(),
+        },
+    );
+    assert!(
+        wordle_result.contains(&Log::builder().payload(SessionEvent::UserWin {
+            user: OTHER_USER.into(),
+            game_id: 0,
+        }))
+    );
+    let reservations: StateQueryReply = session_program
+        .read_state(StateQuery::Reservations)
+        .expect("Failed to read state");
+    assert_eq!(reservations, StateQueryReply::Reservations(vec![]));
+}
+
+#[test]
+pub fn test_reply_from_reservation() {
+    let system = System::new();
+    system.init_logger();
+
+    //机会足够多, 低gas的猜测不会结束游戏
+    let fixture = GameFixture::new(&system, 1_000);
+    let game_id = fixture.start_with_secret(USER, "horse");
+    let other_game_id = fixture.start(OTHER_USER);
+    let reserved_games = || {
+        let reservations: StateQueryReply = fixture
+            .session
+            .read_state(StateQuery::Reservations)
+            .expect("Failed to read state");
+        let StateQueryReply::Reservations(reservations) = reservations else {
+            panic!("unexpected state reply {reservations:?}");
+        };
+        reservations
+            .iter()
+            .map(|reservation| reservation.game_id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(reserved_games(), vec![game_id, other_game_id]);
+
+    //逐步增加gas, 直到被唤醒的消息剩下的gas不够回复, 只能用预留的gas回复
+    let mut replied_from_reservation = false;
+    for gas_limit in (500_000_000..10_000_000_000).step_by(50_000_000) {
+        let result = fixture.session.send_with_gas(
+            USER,
+            CheckWord {
+                user: USER.into(),
+                game_id,
+                word: "house".to_string(),
+            },
+            gas_limit,
+            0,
+        );
+        let checked = result.log().iter().any(|log| {
+            log.destination() == USER.into()
+                && matches!(
+                    SessionEvent::decode(&mut log.payload()),
+                    Ok(SessionEvent::WordChecked { .. })
+                )
+        });
+        if checked && !reserved_games().contains(&game_id) {
+            replied_from_reservation = true;
+            break;
+        }
+    }
+    assert!(
+        replied_from_reservation,
+        "no reply was paid by the reservation"
+    );
+
+    //用完预留的游戏照常继续和结束
+    let feedback = fixture.guess(USER, "horse");
+    assert!(feedback.is_solved(5));
+    assert_eq!(reserved_games(), vec![other_game_id]);
+
+    //强制结束的游戏也取消预留
+    let force_end_result = fixture.session.send(
+        test_support::OWNER,
+        ForceEndGame {
+            player: OTHER_USER.into(),
+            game_id: other_game_id,
+        },
+    );
+    assert!(!force_end_result.main_failed(), "force end failed");
+    assert_eq!(reserved_games(), vec![]);
+}
+
+#[test]
+pub fn test_unexpected_reply() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, _wordle_program) = init_game(&system);
+    //开始游戏时回复的是提示, 不是等待的回复
+    let _mock_program = add_mock_wordle(
+        &system,
+        &session_program,
+        Some(wordle_io::Event::HintRevealed {
+            user: OTHER_USER.into(),
+            game_id: 0,
+            hint: None,
+        }),
+    );
+    let start_result = session_program.send(
+        OTHER_USER,
+        StartGame {
+            user: OTHER_USER.into(),
+            language: Language::En,
+            backend: 1,
+        },
+    );
+    //回复不对和wordle出错一样处理, 用户不会一直等下去
+    assert!(
+        matches!(
+            test_support::reply(&start_result, OTHER_USER),
+            SessionEvent::BackendFailed { backend: 1, .. }
+        ),
+        "{start_result:?}"
+    );
+    let start_result = session_program.send(
+        OTHER_USER,
+        StartGame {
+            user: OTHER_USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    let SessionEvent::GameStarted { game_id, .. } = test_support::reply(&start_result, OTHER_USER)
+    else {
+        panic!("Failed to start a game: {start_result:?}");
+    };
+
+    //强制结束后wordle的回复没有消息等待, 作为事件发出, 不发到owner的邮箱
+    let force_end_result = session_program.send(
+        USER,
+        ForceEndGame {
+            player: OTHER_USER.into(),
+            game_id,
+        },
+    );
+    let dropped = force_end_result.log().iter().any(|log| {
+        log.destination() == gstd::ActorId::zero()
+            && matches!(
+                SessionEvent::decode(&mut log.payload()),
+                Ok(SessionEvent::ReplyDropped { .. })
+            )
+    });
+    assert!(dropped, "the reply should be dropped");
+}
+
+#[test]
+pub fn test_backend_failed() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, _wordle_program) = init_game(&system);
+    let _mock_program = add_mock_backend(&system, &session_program, FailingWordle);
+    //失败的猜测和提示不算进频率限制
+    let limit_result = session_program.send(
+        USER,
+        SetRateLimits(RateLimits {
+            min_blocks_between_guesses: 10,
+            ..RateLimits::default()
+        }),
+    );
+    assert!(!limit_result.main_failed(), "limit failed");
+    let start_game = |backend| {
+        session_program.send(
+            USER,
+            StartGame {
+                user: USER.into(),
+                language: Language::En,
+                backend,
+            },
+        )
+    };
+    //wordle出错时游戏没有开始, 预留也取消了
+    let failed_start_result = start_game(1);
+    assert!(
+        matches!(
+            test_support::reply(&failed_start_result, USER),
+            SessionEvent::BackendFailed { backend: 1, .. }
+        ),
+        "{failed_start_result:?}"
+    );
+    let reservations: StateQueryReply = session_program
+        .read_state(StateQuery::Reservations)
+        .expect("Failed to read state");
+    assert_eq!(reservations, StateQueryReply::Reservations(vec![]));
+
+    let start_result = start_game(0);
+    let SessionEvent::GameStarted { game_id, .. } = test_support::reply(&start_result, USER) else {
+        panic!("Failed to start a game");
+    };
+    let set_backend = |address: u64| {
+        let result = session_program.send(
+            USER,
+            SetBackendAddress {
+                backend: 0,
+                address: address.into(),
+            },
+        );
+        assert!(!result.main_failed(), "set backend failed");
+    };
+    set_backend(MOCK_WORDLE_PROGRAM_ID);
+    for action in [
+        CheckWord {
+            user: USER.into(),
+            game_id,
+            word: "house".to_string(),
+        },
+        RequestHint {
+            user: USER.into(),
+            game_id,
+        },
+    ] {
+        let result = session_program.send(USER, action);
+        assert!(
+            matches!(
+                test_support::reply(&result, USER),
+                SessionEvent::BackendFailed { backend: 0, .. }
+            ),
+            "{result:?}"
+        );
+    }
+    //失败的猜测不算一次猜测, 用户可以继续玩
+    let export_result = session_program.send(
+        USER,
+        ExportState {
+            offset: 0,
+            limit: 10,
+        },
+    );
+    let chunk = exported_chunk(&export_result);
+    assert!(chunk.players[0].open_games[0].guesses.is_empty());
+    set_backend(WORDLE_PROGRAM_ID);
+    let check_result = session_program.send(
+        USER,
+        CheckWord {
+            user: USER.into(),
+            game_id,
+            word: "house".to_string(),
+        },
+    );
+    assert!(
+        matches!(
+            test_support::reply(&check_result, USER),
+            SessionEvent::WordChecked { .. } | SessionEvent::UserWin { .. }
+        ),
+        "{check_result:?}"
+    );
+}
+
+#[test]
+pub fn test_busy_while_waiting() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, _wordle_program) = init_game(&system);
+    //mock不回复, 开始游戏的消息一直在等待
+    let _mock_program = add_mock_wordle(&system, &session_program, None);
+    let start_result = session_program.send(
+        OTHER_USER,
+        StartGame {
+            user: OTHER_USER.into(),
+            language: Language::En,
+            backend: 1,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+
+    for action in [
+        StartGame {
+            user: OTHER_USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+        CheckWord {
+            user: OTHER_USER.into(),
+            game_id: 0,
+            word: "house".to_string(),
+        },
+    ] {
+        let busy_result = session_program.send(OTHER_USER, action);
+        assert!(!busy_result.main_failed(), "busy run failed");
+        assert!(
+            busy_result.contains(&Log::builder().payload(SessionEvent::Busy {
+                user: OTHER_USER.into(),
+            })),
+            "the player should be busy"
+        );
+    }
+
+    //其他用户不受影响
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(
+        start_result.contains(&Log::builder().payload(SessionEvent::GameStarted {
+            user: USER.into(),
+            game_id: 0,
+        })),
+        "start run failed"
+    );
+}
+
+#[test]
+pub fn test_force_end_waiting_game() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, wordle_program) = init_game(&system);
+    let start_result = session_program.send(
+        OTHER_USER,
+        StartGame {
+            user: OTHER_USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+    //猜测发给不回复的mock, 消息一直在等待
+    let _mock_program = add_mock_wordle(&system, &session_program, None);
+    let set_backend = |address: u64| {
+        let result = session_program.send(
+            USER,
+            SetBackendAddress {
+                backend: 0,
+                address: address.into(),
+            },
+        );
+        assert!(!result.main_failed(), "set backend failed");
+    };
+    set_backend(MOCK_WORDLE_PROGRAM_ID);
+    let wordle_result = session_program.send(
+        OTHER_USER,
+        CheckWord {
+            user: OTHER_USER.into(),
+            game_id: 0,
+            word: "house".to_string(),
+        },
+    );
+    assert!(!wordle_result.main_failed(), "wordle run failed");
+    set_backend(WORDLE_PROGRAM_ID);
+
+    //owner和等待的玩家都收到游戏结束的回复
+    let force_end_result = session_program.send(
+        USER,
+        ForceEndGame {
+            player: OTHER_USER.into(),
+            game_id: 0,
+        },
+    );
+    for player in [USER, OTHER_USER] {
+        let replied = force_end_result.log().iter().any(|log| {
+            log.destination() == player.into()
+                && matches!(
+                    SessionEvent::decode(&mut log.payload()),
+                    Ok(SessionEvent::GameForceEnded { game_id: 0, .. })
+                )
+        });
+        assert!(replied, "{player} got no reply: {force_end_result:?}");
+    }
+
+    //wordle里的游戏也结束了
+    let export_result = wordle_program.send(
+        USER,
+        wordle_io::Action::ExportState {
+            offset: 0,
+            limit: 10,
+        },
+    );
+    let exported = export_result.log().iter().find_map(|log| {
+        match wordle_io::Event::decode(&mut log.payload()) {
+            Ok(wordle_io::Event::StateExported(chunk)) => Some(chunk.games),
+            _ => None,
+        }
+    });
+    assert_eq!(exported, Some(vec![]), "{export_result:?}");
+
+    //玩家不再忙, 可以开始新游戏
+    let start_result = session_program.send(
+        OTHER_USER,
+        StartGame {
+            user: OTHER_USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(
+        start_result.contains(&Log::builder().payload(SessionEvent::GameStarted {
+            user: OTHER_USER.into(),
+            game_id: 1,
+        })),
+        "{start_result:?}"
+    );
+}
+
+#[test]
+pub fn test_owner_acts_while_waiting() {
+    let system = System::new();
+    system.init_logger();
+
+    //owner自己也在玩, 猜测发给不回复的mock, 一直在等待
+    let (session_program, _wordle_program) = init_game(&system);
+    let _mock_program = add_mock_wordle(&system, &session_program, None);
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    let SessionEvent::GameStarted { game_id, .. } = test_support::reply(&start_result, USER) else {
+        panic!("Failed to start a game");
+    };
+    let set_backend_result = session_program.send(
+        USER,
+        SetBackendAddress {
+            backend: 0,
+            address: MOCK_WORDLE_PROGRAM_ID.into(),
+        },
+    );
+    assert!(!set_backend_result.main_failed(), "set backend failed");
+    let wordle_result = session_program.send(
+        USER,
+        CheckWord {
+            user: USER.into(),
+            game_id,
+            word: "house".to_string(),
+        },
+    );
+    assert!(!wordle_result.main_failed(), "wordle run failed");
+
+    //等待中的owner也能紧急暂停
+    let pause_result = session_program.send(
+        USER,
+        SetPaused {
+            new_games: true,
+            guesses: false,
+        },
+    );
+    let SessionEvent::ConfigChanged(config) = test_support::reply(&pause_result, USER) else {
+        panic!("the owner should be able to pause: {pause_result:?}");
+    };
+    assert!(config.new_games_paused);
+
+    //结束自己等待的游戏, 等待的消息也收到结束的回复
+    let force_end_result = session_program.send(
+        USER,
+        ForceEndGame {
+            player: USER.into(),
+            game_id,
+        },
+    );
+    let force_ended = force_end_result
+        .log()
+        .iter()
+        .filter(|log| {
+            log.destination() == USER.into()
+                && matches!(
+                    SessionEvent::decode(&mut log.payload()),
+                    Ok(SessionEvent::GameForceEnded { .. })
+                )
+        })
+        .count();
+    assert_eq!(force_ended, 2, "{force_end_result:?}");
+
+    let start_result = session_program.send(
+        USER,
+        StartGame {
+            user: USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(
+        start_result.contains(&Log::builder().payload(SessionEvent::Paused { user: USER.into() })),
+        "{start_result:?}"
+    );
+}
+
+#[test]
+pub fn test_signal_while_waiting() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, _wordle_program) = init_game(&system);
+    //猜测发给不回复的mock, 消息一直等到gas用完
+    let _mock_program = add_mock_wordle(&system, &session_program, None);
+    let set_backend = |address: u64| {
+        let result = session_program.send(
+            USER,
+            SetBackendAddress {
+                backend: 0,
+                address: address.into(),
+            },
+        );
+        assert!(!result.main_failed(), "set backend failed");
+    };
+
+    //逐步增加gas, 第一次能等待的猜测剩下的gas最少, 很快就会从等待列表里移除
+    let mut waiting = None;
+    for (attempt, gas_limit) in (1_000_000_000..5_000_000_000u64)
+        .step_by(10_000_000)
+        .enumerate()
+    {
+        let player = 100 + attempt as u64;
+        set_backend(WORDLE_PROGRAM_ID);
+        let start_result = session_program.send(
+            player,
+            StartGame {
+                user: player.into(),
+                language: Language::En,
+                backend: 0,
+            },
+        );
+        let SessionEvent::GameStarted { game_id, .. } = test_support::reply(&start_result, player)
+        else {
+            panic!("Failed to start a game");
+        };
+        set_backend(MOCK_WORDLE_PROGRAM_ID);
+        let wordle_result = session_program.send_with_gas(
+            player,
+            CheckWord {
+                user: player.into(),
+                game_id,
+                word: "house".to_string(),
+            },
+            gas_limit,
+            0,
+        );
+        if !wordle_result.main_failed() {
+            waiting = Some((player, game_id));
+            break;
+        }
+    }
+    let (player, game_id) = waiting.expect("the guess never waited");
+    set_backend(WORDLE_PROGRAM_ID);
+
+    let signaled = system
+        .spend_blocks(200_000)
+        .iter()
+        .flat_map(|result| result.log())
+        .any(|log| {
+            log.destination() == player.into()
+                && SessionSignal::decode(&mut log.payload()).ok()
+                    == Some(SessionSignal::GuessPending {
+                        user: player.into(),
+                        game_id,
+                    })
+        });
+    assert!(signaled, "the player got no signal");
+
+    //猜测没有回滚, wordle回复之前玩家一直在等
+    let busy_result = session_program.send(
+        player,
+        CheckWord {
+            user: player.into(),
+            game_id,
+            word: "house".to_string(),
+        },
+    );
+    assert!(
+        busy_result.contains(&Log::builder().payload(SessionEvent::Busy {
+            user: player.into(),
+        })),
+        "{busy_result:?}"
+    );
+
+    //强制结束游戏, 失败的消息不用唤醒, 由玩家的下一条消息收到结束的回复
+    let force_end_result = session_program.send(
+        USER,
+        ForceEndGame {
+            player: player.into(),
+            game_id,
+        },
+    );
+    assert!(!force_end_result.main_failed(), "force end failed");
+    let start_game = || {
+        session_program.send(
+            player,
+            StartGame {
+                user: player.into(),
+                language: Language::En,
+                backend: 0,
+            },
+        )
+    };
+    let ended_result = start_game();
+    assert!(
+        matches!(
+            test_support::reply(&ended_result, player),
+            SessionEvent::GameForceEnded { .. }
+        ),
+        "{ended_result:?}"
+    );
+    let start_result = start_game();
+    assert!(
+        matches!(
+            test_support::reply(&start_result, player),
+            SessionEvent::GameStarted { .. }
+        ),
+        "{start_result:?}"
+    );
+}
+
+#[test]
+pub fn test_rate_limits() {
+    let system = System::new();
+    system.init_logger();
+
+    let (session_program, wordle_program) = init_game(&system);
+    fix_secret(&wordle_program, OTHER_USER, "horse");
+    let rate_limits = RateLimits {
+        min_blocks_between_guesses: 10,
+        max_games_per_epoch: 1,
+        epoch_length: 1_000,
+    };
+    let forbidden_result = session_program.send(OTHER_USER, SetRateLimits(rate_limits));
+    assert!(forbidden_result.main_failed(), "only the owner can limit");
+    let limit_result = session_program.send(USER, SetRateLimits(rate_limits));
+    assert!(!limit_result.main_failed(), "limit failed");
+
+    let start_result = session_program.send(
+        OTHER_USER,
+        StartGame {
+            user: OTHER_USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(!start_result.main_failed(), "start run failed");
+    //一个周期只能开始一局游戏
+    let start_result = session_program.send(
+        OTHER_USER,
+        StartGame {
+            user: OTHER_USER.into(),
+            language: Language::En,
+            backend: 0,
+        },
+    );
+    assert!(
+        start_result.contains(&Log::builder().payload(SessionEvent::RateLimited {
+            user: OTHER_USER.into(),
+            error: RateLimitError::TooManyGames { next_block: 1_000 },
+        })),
+        "the second game shouldn't start"
+    );
+
+    let wordle_result = session_program.send(
+        OTHER_USER,
+        CheckWord {
+            user: OTHER_USER.into(),
+            game_id: 0,
+            word: "house".to_string(),
+        },
+    );
+    assert!(!wordle_result.main_failed(), "wordle run failed");
+    //两次猜测之间至少间隔10个区块, 被拒绝的猜测不算一次机会
+    let wordle_result = session_program.send(
+        OTHER_USER,
+        CheckWord {
+            user: OTHER_USER.into(),
+            game_id: 0,
+            word: "human".to_string(),
+        },
+    );
+    let too_soon = wordle_result.log().iter().any(|log| {
+        matches!(
+            SessionEvent::decode(&mut log.payload()),
+            Ok(SessionEvent::RateLimited {
+                error: RateLimitError::TooSoon { .. },
+                ..
+            })
+        )
+    });
+    assert!(too_soon, "the guess should be too soon");
+    //提示和猜测一样限制频率
+    let hint_result = session_program.send(
+        OTHER_USER,
+        RequestHint {
+            user: OTHER_USER.into(),
+            game_id: 0,
+        },
+    );
+    assert!(
+        matches!(
+            test_support::reply(&hint_result, OTHER_USER),
+            SessionEvent::RateLimited {
+                error: RateLimitError::TooSoon { .. },
+                ..
+            }
+        ),
+        "{hint_result:?}"
+    );
+
+    system.spend_blocks(10);
+    let wordle_result = session_program.send(
+        OTHER_USER,
+        CheckWord {
+            user: OTHER_USER.into(),
+            game_id: 0,
+            word: "horse".to_string(),
+        },
+    );
+    assert!(
+        wordle_result.contains(&Log::builder().payload(SessionEvent::UserWin {
+            user: OTHER_USER.into(),
+            game_id: 0,
+        })),
+        "the guess should be accepted"
+    );
+    let games: StateQueryReply = session_program
+        .read_state(StateQuery::History {
+            player: OTHER_USER.into(),
+            offset: 0,
+            limit: 1,
+        })
+        .expect("Failed to read state");
+    let StateQueryReply::History(games) = games else {
+        panic!("unexpected state reply");
+    };
+    //只记录了被接受的两次猜测
+    let [game] = games.as_slice() else {
+        panic!("the game should be in the history");
+    };
+    let words: Vec<_> = game
+        .guesses
+        .iter()
+        .map(|guess| guess.word.as_str())
+        .collect();
+    assert_eq!(words, ["house", "horse"]);
+    assert!(game.hints.is_empty());
+}
+
+#[test]
+pub fn test_hard_mode() {
+    let system = System::new();
+    system.init_logger();
+
+    let fixture = GameFixture::new(&system, 3);
+    let forbidden_result = fixture.session.send(OTHER_USER, SetHardMode(true));
+    assert!(
+        forbidden_result.main_failed(),
+        "only the owner can change the mode"
+    );
+    let hard_mode_result = fixture.session.send(test_support::OWNER, SetHardMode(true));
+    let SessionEvent::ConfigChanged(config) =
+        test_support::reply(&hard_mode_result, test_support::OWNER)
+    else {
+        panic!("the config should change");
+    };
+    assert!(config.hard_mode);
+
+    let game_id = fixture.start_with_secret(USER, "horse");
+    fixture.guess(USER, "house");
+    //第二个字母已经猜中, 必须保留
+    let hard_result = fixture.session.send(
+        USER,
+        CheckWord {
+            user: USER.into(),
+            game_id,
+            word: "human".to_string(),
+        },
+    );
+    assert!(
+        hard_result.contains(&Log::builder().payload(SessionEvent::InvalidGuess {
+            user: USER.into(),
+            game_id,
+            error: GuessError::HardMode(HardModeError::MissingCorrectLetter {
+                position: 1,
+                letter: "o".to_string(),
+            }),
+        }))
+    );
+    //被拒绝的猜测不算一次机会
+    let feedback = fixture.guess(USER, "horse");
+    assert_eq!(feedback.correct_positions, vec![0, 1, 2, 3, 4]);
+    let record = fixture
+        .record(USER, game_id)
+        .expect("the game should be over");
+    assert_eq!(record.outcome, GameOutcome::Win);
+    assert_eq!(record.guesses.len(), 2);
+}
+
+#[test]
+pub fn test_fixture_fixed_secret() {
+    let system = System::new();
+    system.init_logger();
+
+    let fixture = GameFixture::new(&system, 3);
+    let game_id = fixture.start_with_secret(USER, "human");
+    let feedback = fixture.guess(USER, "house");
+    assert_eq!(feedback.correct_positions, vec![0]);
+    assert_eq!(feedback.contained_in_word, vec![2]);
+    let feedback = fixture.guess(USER, "human");
+    assert_eq!(feedback.correct_positions, vec![0, 1, 2, 3, 4]);
+    let record = fixture
+        .record(USER, game_id)
+        .expect("the game should be over");
+    assert_eq!(record.secret, "human");
+    assert_eq!(record.outcome, GameOutcome::Win);
+}
+
+#[test]
+pub fn test_word_not_in_list() {
+    let system = System::new();
+    system.init_logger();
+
+    let fixture = GameFixture::new(&system, 2);
+    let allowed: Vec<_> = ["crane", "slate"]
+        .into_iter()
+        .map(|word| Language::En.pack(word).expect("an English word"))
+        .collect();
+    fixture.upload_words(Language::En, wordle_io::WordList::Allowed, &allowed);
+    let game_id = fixture.start_with_secret(USER, "horse");
+
+    //不在列表里的单词不算一次猜测
+    let result = fixture.session.send(
+        USER,
+        CheckWord {
+            user: USER.into(),
+            game_id,
+            word: "house".to_string(),
+        },
+    );
+    assert!(
+        result.contains(&Log::builder().payload(SessionEvent::InvalidGuess {
+            user: USER.into(),
+            game_id,
+            error: GuessError::NotInWordList,
+        })),
+        "{result:?}"
+    );
+    let feedback = fixture.guess(USER, "slate");
+    assert_eq!(feedback.correct_positions, vec![4]);
+    let feedback = fixture.guess(USER, "horse");
+    assert!(feedback.is_solved(5));
+    let record = fixture
+        .record(USER, game_id)
+        .expect("the game should be over");
+    assert_eq!(record.outcome, GameOutcome::Win);
+    assert_eq!(record.guesses.len(), 2);
 }
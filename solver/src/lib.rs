@@ -0,0 +1,101 @@
+#![no_std]
+
+//! A solver for the feedback model of the wordle program.
+//!
+//! Given a word list and the feedback of the guesses made so far, [`Solver`] narrows down the
+//! words that can still be the secret and ranks the next guesses by the expected information
+//! they give about it.
+
+extern crate alloc;
+
+use alloc::{collections::BTreeMap, vec::Vec};
+pub use scoring::{score, Feedback};
+
+/// The expected information, in bits, that guessing `guess` gives about a secret that is one of
+/// `candidates`, each equally likely.
+pub fn expected_information(guess: &str, candidates: &[&str]) -> f64 {
+    let mut patterns: BTreeMap<Feedback, u32> = BTreeMap::new();
+    for candidate in candidates {
+        *patterns.entry(score(candidate, guess)).or_default() += 1;
+    }
+    let total = candidates.len() as f64;
+    patterns
+        .values()
+        .map(|count| {
+            let p = f64::from(*count) / total;
+            -p * libm::log2(p)
+        })
+        .sum()
+}
+
+/// Narrows down the secret word from the feedback of the guesses made so far.
+#[derive(Clone, Debug)]
+pub struct Solver<'a> {
+    words: &'a [&'a str],
+    candidates: Vec<&'a str>,
+}
+
+impl<'a> Solver<'a> {
+    /// Creates a solver for a secret taken from `words`, which are also the allowed guesses.
+    pub fn new(words: &'a [&'a str]) -> Self {
+        Self {
+            words,
+            candidates: words.to_vec(),
+        }
+    }
+
+    /// Keeps only the candidates that would have given `feedback` for `guess`.
+    pub fn apply(&mut self, guess: &str, feedback: &Feedback) {
+        self.candidates
+            .retain(|candidate| score(candidate, guess) == *feedback);
+    }
+
+    /// Applies the feedback of every guess in `history`, oldest first.
+    pub fn apply_history<'b>(
+        &mut self,
+        history: impl IntoIterator<Item = (&'b str, &'b Feedback)>,
+    ) {
+        for (guess, feedback) in history {
+            self.apply(guess, feedback);
+        }
+    }
+
+    /// The words that can still be the secret.
+    pub fn candidates(&self) -> &[&'a str] {
+        &self.candidates
+    }
+
+    /// All words with the expected information they give, the most informative first.
+    ///
+    /// Among equally informative words, the ones that can still be the secret come first, as
+    /// they may win right away.
+    pub fn rank_guesses(&self) -> Vec<(&'a str, f64)> {
+        let mut ranked: Vec<(&'a str, f64)> = self
+            .words
+            .iter()
+            .map(|word| (*word, expected_information(word, &self.candidates)))
+            .collect();
+        ranked.sort_by(|(a, a_bits), (b, b_bits)| {
+            b_bits
+                .total_cmp(a_bits)
+                .then_with(|| {
+                    self.candidates
+                        .contains(b)
+                        .cmp(&self.candidates.contains(a))
+                })
+                .then_with(|| a.cmp(b))
+        });
+        ranked
+    }
+
+    /// The best next guess, or [`None`] if no word fits the feedback.
+    ///
+    /// When a single candidate is left, it's the secret.
+    pub fn best_guess(&self) -> Option<&'a str> {
+        match self.candidates.as_slice() {
+            [] => None,
+            [secret] => Some(secret),
+            _ => self.rank_guesses().first().map(|(word, _)| *word),
+        }
+    }
+}
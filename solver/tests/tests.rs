@@ -0,0 +1,78 @@
+use solver::{expected_information, score, Feedback, Solver};
+
+const WORDS: [&str; 6] = ["house", "human", "horse", "mouse", "louse", "hobby"];
+
+fn feedback(correct_positions: &[u8], contained_in_word: &[u8]) -> Feedback {
+    Feedback {
+        correct_positions: correct_positions.to_vec(),
+        contained_in_word: contained_in_word.to_vec(),
+    }
+}
+
+#[test]
+fn test_score() {
+    assert_eq!(score("horse", "house"), feedback(&[0, 1, 3, 4], &[]));
+    assert_eq!(score("horse", "horse"), feedback(&[0, 1, 2, 3, 4], &[]));
+    assert_eq!(score("human", "house"), feedback(&[0], &[2]));
+    //重复的字母每一个都算包含
+    assert_eq!(score("human", "mamma"), feedback(&[2], &[0, 1, 3, 4]));
+    assert_eq!(score("слово", "место"), feedback(&[4], &[2]));
+    assert!(score("horse", "horse").is_solved(5));
+    assert!(!score("horse", "house").is_solved(5));
+}
+
+#[test]
+fn test_filter_candidates() {
+    let mut solver = Solver::new(&WORDS);
+    assert_eq!(solver.candidates(), WORDS);
+
+    solver.apply("house", &score("louse", "house"));
+    assert_eq!(solver.candidates(), ["mouse", "louse"]);
+    solver.apply("mouse", &score("louse", "mouse"));
+    assert_eq!(solver.candidates(), ["louse"]);
+    assert_eq!(solver.best_guess(), Some("louse"));
+
+    //没有单词符合反馈
+    solver.apply("hobby", &feedback(&[0, 1, 2, 3, 4], &[]));
+    assert!(solver.candidates().is_empty());
+    assert_eq!(solver.best_guess(), None);
+}
+
+#[test]
+fn test_apply_history() {
+    let history = [
+        ("hobby", score("horse", "hobby")),
+        ("house", score("horse", "house")),
+    ];
+    let mut solver = Solver::new(&WORDS);
+    solver.apply_history(history.iter().map(|(guess, feedback)| (*guess, feedback)));
+    assert_eq!(solver.candidates(), ["horse"]);
+}
+
+#[test]
+fn test_expected_information() {
+    //每个候选单词的反馈都不同时信息量最大
+    let candidates = ["house", "mouse", "horse", "human"];
+    let bits = expected_information("house", &candidates);
+    assert!((bits - 2.0).abs() < 1e-9, "{bits}");
+    //所有候选单词的反馈都相同时没有信息
+    assert_eq!(expected_information("hobby", &["mouse", "louse"]), 0.0);
+    assert_eq!(expected_information("house", &[]), 0.0);
+}
+
+#[test]
+fn test_rank_guesses() {
+    let solver = Solver::new(&WORDS);
+    let ranked = solver.rank_guesses();
+    assert_eq!(ranked.len(), WORDS.len());
+    assert!(ranked.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    assert_eq!(solver.best_guess(), Some(ranked[0].0));
+
+    let mut solver = Solver::new(&WORDS);
+    solver.apply("hobby", &score("mouse", "hobby"));
+    assert_eq!(solver.candidates(), ["mouse", "louse"]);
+    //信息量相同时优先猜可能是答案的单词
+    let (best, bits) = solver.rank_guesses()[0];
+    assert_eq!(bits, 1.0);
+    assert!(solver.candidates().contains(&best));
+}
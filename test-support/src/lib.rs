@@ -0,0 +1,210 @@
+//! Fixtures for testing the session and wordle programs together in `gtest`.
+//!
+//! [`GameFixture`] deploys both programs. With the `test-hooks` feature, which builds the wordle
+//! program with its test hooks, it lets tests choose the secret word of a game, so they don't
+//! depend on the random word bank.
+
+use gstd::Decode;
+use gtest::{Program, RunResult, System};
+use scoring::Feedback;
+use session_io::{
+    parse_positions, Action, GameId, GameRecord, GameSessionInit, Language, SessionEvent,
+    StateQuery, StateQueryReply,
+};
+use std::{cell::RefCell, collections::BTreeMap};
+use wordle_io::{PackedWord, WordList};
+
+pub use session::WASM_BINARY as SESSION_WASM_BINARY;
+pub use wordle::WASM_BINARY as WORDLE_WASM_BINARY;
+
+pub const SESSION_PROGRAM_ID: u64 = 1;
+pub const WORDLE_PROGRAM_ID: u64 = 2;
+/// The account that deploys and owns both programs in a [`GameFixture`].
+pub const OWNER: u64 = 10;
+/// The number of words [`GameFixture::upload_words`] sends in one message.
+pub const WORDS_CHUNK: usize = 2_000;
+
+/// Deploys the wordle program and the session using it, both owned by `owner`, and lets the
+/// session play on the wordle program.
+///
+/// Returns the session and the wordle program.
+pub fn deploy(system: &System, owner: u64, max_play_times: u32) -> (Program<'_>, Program<'_>) {
+    let session_program =
+        Program::from_binary_with_id(system, SESSION_PROGRAM_ID, SESSION_WASM_BINARY);
+    let wordle_program =
+        Program::from_binary_with_id(system, WORDLE_PROGRAM_ID, WORDLE_WASM_BINARY);
+
+    let wordle_init_result = wordle_program.send_bytes(owner, []);
+    assert!(!wordle_init_result.main_failed(), "wordle init failed");
+
+    let session_init_result = session_program.send(
+        owner,
+        GameSessionInit {
+            wordle_address: WORDLE_PROGRAM_ID.into(),
+            max_play_times,
+        },
+    );
+    assert!(!session_init_result.main_failed(), "session init failed");
+
+    let add_session_result = wordle_program.send(
+        owner,
+        wordle_io::Action::AddSession(SESSION_PROGRAM_ID.into()),
+    );
+    assert!(
+        !add_session_result.main_failed(),
+        "adding the session failed"
+    );
+    (session_program, wordle_program)
+}
+
+/// The session and wordle programs deployed by [`OWNER`], with helpers to play games.
+pub struct GameFixture<'a> {
+    pub session: Program<'a>,
+    pub wordle: Program<'a>,
+    /// The latest game each player started.
+    games: RefCell<BTreeMap<u64, GameId>>,
+}
+
+impl<'a> GameFixture<'a> {
+    pub fn new(system: &'a System, max_play_times: u32) -> Self {
+        let (session, wordle) = deploy(system, OWNER, max_play_times);
+        Self {
+            session,
+            wordle,
+            games: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Starts an English game of `user` with a random secret word.
+    pub fn start(&self, user: u64) -> GameId {
+        let result = self.session.send(
+            user,
+            Action::StartGame {
+                user: user.into(),
+                language: Language::En,
+                backend: 0,
+            },
+        );
+        let game_id = match reply(&result, user) {
+            SessionEvent::GameStarted { game_id, .. } => game_id,
+            event => panic!("Failed to start a game: {event:?}"),
+        };
+        self.games.borrow_mut().insert(user, game_id);
+        game_id
+    }
+
+    /// Starts an English game of `user` with the `secret` word.
+    #[cfg(feature = "test-hooks")]
+    pub fn start_with_secret(&self, user: u64, secret: &str) -> GameId {
+        self.fix_secret(user, Some(secret));
+        let game_id = self.start(user);
+        self.fix_secret(user, None);
+        game_id
+    }
+
+    /// Makes the games `user` starts use `secret`, or random words again if it's [`None`].
+    #[cfg(feature = "test-hooks")]
+    pub fn fix_secret(&self, user: u64, secret: Option<&str>) {
+        let result = self.wordle.send(
+            OWNER,
+            wordle_io::Action::FixSecret {
+                user: user.into(),
+                word: secret.map(str::to_string),
+            },
+        );
+        assert!(!result.main_failed(), "Failed to fix the secret");
+    }
+
+    /// Restarts the random word selection of the wordle program from `seed`.
+    #[cfg(feature = "test-hooks")]
+    pub fn set_seed(&self, seed: u8) {
+        let result = self.wordle.send(OWNER, wordle_io::Action::SetSeed(seed));
+        assert!(!result.main_failed(), "Failed to set the seed");
+    }
+
+    /// Replaces a word list of the wordle program with `words`, uploading them in chunks.
+    pub fn upload_words(&self, language: Language, list: WordList, words: &[PackedWord]) {
+        let result = self
+            .wordle
+            .send(OWNER, wordle_io::Action::ClearWords { language, list });
+        assert!(!result.main_failed(), "Failed to clear the words");
+        for chunk in words.chunks(WORDS_CHUNK) {
+            let result = self.wordle.send(
+                OWNER,
+                wordle_io::Action::AddWords {
+                    language,
+                    list,
+                    words: chunk.to_vec(),
+                },
+            );
+            assert!(!result.main_failed(), "Failed to add the words");
+        }
+    }
+
+    /// Makes a guess in the latest game of `user`, returning the feedback for it.
+    ///
+    /// Panics if the guess is rejected.
+    pub fn guess(&self, user: u64, word: &str) -> Feedback {
+        let game_id = *self
+            .games
+            .borrow()
+            .get(&user)
+            .expect("The user has no game");
+        let result = self.session.send(
+            user,
+            Action::CheckWord {
+                user: user.into(),
+                game_id,
+                word: word.to_string(),
+            },
+        );
+        match reply(&result, user) {
+            SessionEvent::WordChecked {
+                correct_positions,
+                contained_in_word,
+                ..
+            } => Feedback {
+                correct_positions: parse_positions(&correct_positions).expect("Invalid positions"),
+                contained_in_word: parse_positions(&contained_in_word).expect("Invalid positions"),
+            },
+            //游戏结束后反馈记录在历史里
+            SessionEvent::UserWin { .. } | SessionEvent::UserLose { .. } => {
+                let guess = self
+                    .record(user, game_id)
+                    .and_then(|mut record| record.guesses.pop())
+                    .expect("The finished game isn't in the history");
+                Feedback {
+                    correct_positions: guess.correct_positions,
+                    contained_in_word: guess.contained_in_word,
+                }
+            }
+            event => panic!("The guess was rejected: {event:?}"),
+        }
+    }
+
+    /// A finished game of `user` from the history.
+    pub fn record(&self, user: u64, game_id: GameId) -> Option<GameRecord> {
+        let reply: StateQueryReply = self
+            .session
+            .read_state(StateQuery::Game {
+                player: user.into(),
+                game_id,
+            })
+            .expect("Failed to read state");
+        match reply {
+            StateQueryReply::Game(record) => record,
+            reply => panic!("Unexpected state reply: {reply:?}"),
+        }
+    }
+}
+
+/// The reply of the session to `user` in `result`.
+pub fn reply(result: &RunResult, user: u64) -> SessionEvent {
+    assert!(!result.main_failed(), "The session failed: {result:?}");
+    result
+        .log()
+        .iter()
+        .filter(|log| log.destination() == user.into())
+        .find_map(|log| SessionEvent::decode(&mut log.payload()).ok())
+        .expect("The session didn't reply")
+}
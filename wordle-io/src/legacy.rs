@@ -0,0 +1,93 @@
+//! The layouts of [`StateChunk`]s exported by earlier versions of the wordle program, decoded by
+//! [`StateChunk`]'s `Decode` implementation and upgraded to the current one.
+
+use super::*;
+
+/// Version 1, before hints.
+#[derive(Decode)]
+#[codec(crate = gstd::codec)]
+pub(crate) struct StateChunkV1 {
+    games: Vec<GameStateV1>,
+    next_game_id: GameId,
+    next: Option<u32>,
+}
+
+#[derive(Decode)]
+#[codec(crate = gstd::codec)]
+struct GameStateV1 {
+    game_id: GameId,
+    user: ActorId,
+    word: String,
+}
+
+impl StateChunkV1 {
+    pub(crate) fn upgrade(self) -> StateChunk {
+        StateChunk {
+            version: 1,
+            games: self
+                .games
+                .into_iter()
+                .map(|game| GameState {
+                    game_id: game.game_id,
+                    user: game.user,
+                    language: language_of(&game.word),
+                    word: game.word,
+                    known_positions: vec![],
+                })
+                .collect(),
+            next_game_id: self.next_game_id,
+            next: self.next,
+        }
+    }
+}
+
+/// Version 2, before games stored their language.
+#[derive(Decode)]
+#[codec(crate = gstd::codec)]
+pub(crate) struct StateChunkV2 {
+    games: Vec<GameStateV2>,
+    next_game_id: GameId,
+    next: Option<u32>,
+}
+
+#[derive(Decode)]
+#[codec(crate = gstd::codec)]
+struct GameStateV2 {
+    game_id: GameId,
+    user: ActorId,
+    word: String,
+    known_positions: Vec<u8>,
+}
+
+impl StateChunkV2 {
+    pub(crate) fn upgrade(self) -> StateChunk {
+        StateChunk {
+            version: 2,
+            games: self
+                .games
+                .into_iter()
+                .map(|game| GameState {
+                    game_id: game.game_id,
+                    user: game.user,
+                    language: language_of(&game.word),
+                    word: game.word,
+                    known_positions: game.known_positions,
+                })
+                .collect(),
+            next_game_id: self.next_game_id,
+            next: self.next,
+        }
+    }
+}
+
+/// The first language whose alphabet has every letter of the secret `word`. A word made only of
+/// letters English has too is taken as English.
+fn language_of(word: &str) -> Language {
+    [Language::En, Language::De, Language::Es, Language::Ru]
+        .into_iter()
+        .find(|language| {
+            word.chars()
+                .all(|letter| language.alphabet().contains(letter))
+        })
+        .unwrap_or_default()
+}
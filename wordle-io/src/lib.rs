@@ -3,6 +3,8 @@
 use gmeta::{InOut, Metadata, Out};
 use gstd::{prelude::*, ActorId, MessageId};
 
+mod legacy;
+
 /// The contract metadata. Used by frontend apps & for describing the types of messages that can be
 /// sent in contract's entry points. See also [`Metadata`].
 pub struct WordleMetadata;
@@ -31,12 +33,218 @@ impl Metadata for WordleMetadata {
 
 pub type State = Vec<(ActorId, u128)>;
 
+/// Identifier of a game, unique within one wordle program.
+pub type GameId = u64;
+
+/// The version of the layout of [`StateChunk`]s produced by this crate.
+///
+/// Bumped on every change of the exported state, so an upgraded program can tell which layout
+/// it imports.
+pub const SCHEMA_VERSION: u16 = 3;
+
+/// The number of letters of the words in word lists.
+pub const WORD_LENGTH: usize = 5;
+
+/// A word of [`WORD_LENGTH`] letters packed as the positions of its letters in the alphabet of
+/// its language, read as a number in base of the alphabet's size with the first letter being the
+/// most significant.
+///
+/// Packed words of a language sort in the same order as the words, so sorted lists of them can
+/// be binary searched.
+pub type PackedWord = u32;
+
+/// The word bank a game picks its secret word from.
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum Language {
+    #[default]
+    En,
+    De,
+    Es,
+    Ru,
+}
+
+impl Language {
+    /// The lowercase letters words of this language are made of.
+    pub fn alphabet(&self) -> &'static str {
+        match self {
+            Language::En => "abcdefghijklmnopqrstuvwxyz",
+            Language::De => "abcdefghijklmnopqrstuvwxyzäöüß",
+            Language::Es => "abcdefghijklmnñopqrstuvwxyz",
+            Language::Ru => "абвгдеёжзийклмнопрстуфхцчшщъыьэюя",
+        }
+    }
+
+    /// Packs a word of [`WORD_LENGTH`] letters of this language, or returns [`None`] if it isn't
+    /// one.
+    pub fn pack(&self, word: &str) -> Option<PackedWord> {
+        let alphabet = self.alphabet();
+        let base = alphabet.chars().count() as PackedWord;
+        let mut packed = 0;
+        let mut length = 0;
+        for letter in word.chars() {
+            if length == WORD_LENGTH {
+                return None;
+            }
+            let index = alphabet.chars().position(|c| c == letter)? as PackedWord;
+            packed = packed * base + index;
+            length += 1;
+        }
+        (length == WORD_LENGTH).then_some(packed)
+    }
+
+    /// Unpacks a word packed by [`Language::pack`], or returns [`None`] if `packed` is too large
+    /// for a word of this language.
+    pub fn unpack(&self, packed: PackedWord) -> Option<String> {
+        let letters: Vec<char> = self.alphabet().chars().collect();
+        let base = letters.len() as PackedWord;
+        let mut rest = packed;
+        let mut word = ['\0'; WORD_LENGTH];
+        for letter in word.iter_mut().rev() {
+            *letter = letters[(rest % base) as usize];
+            rest /= base;
+        }
+        (rest == 0).then(|| word.iter().collect())
+    }
+}
+
+/// A word list of a language in the wordle program.
+#[derive(Encode, Decode, TypeInfo, Clone, Copy, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub enum WordList {
+    /// The words secrets are picked from. Starts with the built-in bank of the language.
+    Secrets,
+    /// The words that can be guessed besides the secrets. Every word of the alphabet can be
+    /// guessed while it's empty, as it is at first.
+    Allowed,
+}
+
 #[derive(Encode, Decode, TypeInfo, Clone, Debug)]
 #[codec(crate = gstd::codec)]
 #[scale_info(crate = gstd::scale_info)]
 pub enum Action {
-    StartGame { user: ActorId },
-    CheckWord { user: ActorId, word: String },
+    /// Starts a game of `user`, who is trusted to be the player the sender acts for.
+    ///
+    /// Only the owner and sessions added with [`Action::AddSession`] can play, which goes for
+    /// [`Action::CheckWord`], [`Action::RevealHint`] and [`Action::EndGame`] too.
+    StartGame { user: ActorId, language: Language },
+    CheckWord {
+        user: ActorId,
+        game_id: GameId,
+        word: String,
+        /// Ends the game after this guess even if it's wrong.
+        last_attempt: bool,
+    },
+    /// Exports up to `limit` games, starting from the `offset`th one.
+    ///
+    /// Only the owner can export the state.
+    ExportState { offset: u32, limit: u32 },
+    /// Imports a chunk of state exported from the previous version of the program.
+    ///
+    /// Games already imported are skipped, so a chunk can be sent again. A game whose id is
+    /// already used by another game, e.g. one started on this program, is rejected.
+    ///
+    /// Only the owner can import the state.
+    ImportState(StateChunk),
+    /// Hands the program over to `new_owner`.
+    ///
+    /// Only the owner can transfer the ownership.
+    TransferOwnership { new_owner: ActorId },
+    /// Reveals a letter of the secret word that wasn't guessed or revealed yet.
+    RevealHint { user: ActorId, game_id: GameId },
+    /// Adds a chunk of words to a list of `language`, so lists of any size can be uploaded over
+    /// several messages.
+    ///
+    /// Only the owner can change the word lists.
+    AddWords {
+        language: Language,
+        list: WordList,
+        words: Vec<PackedWord>,
+    },
+    /// Empties a list of `language`, e.g. before uploading a new one.
+    ///
+    /// Only the owner can change the word lists.
+    ClearWords { language: Language, list: WordList },
+    /// Lets the session program at this address play games for its players.
+    ///
+    /// Only the owner can add sessions.
+    AddSession(ActorId),
+    /// Stops the session program at this address from playing. Its games stay open.
+    ///
+    /// Only the owner can remove sessions.
+    RemoveSession(ActorId),
+    /// Ends a game without revealing the secret, e.g. because the session force-ended it. A game
+    /// that is already over is ignored.
+    EndGame { user: ActorId, game_id: GameId },
+    /// Makes every game `user` starts from now on use `word` as the secret, whatever the
+    /// language, until it's fixed to [`None`].
+    ///
+    /// Only the owner can fix secrets, and only in programs built with the `test-hooks` feature.
+    #[cfg(feature = "test-hooks")]
+    FixSecret { user: ActorId, word: Option<String> },
+    /// Restarts the random word selection from `seed`, so the same games pick the same words.
+    ///
+    /// Only the owner can set the seed, and only in programs built with the `test-hooks` feature.
+    #[cfg(feature = "test-hooks")]
+    SetSeed(u8),
+}
+
+/// A game that hasn't finished yet, as it's stored in a [`StateChunk`].
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct GameState {
+    pub game_id: GameId,
+    pub user: ActorId,
+    pub language: Language,
+    pub word: String,
+    /// Positions already guessed correctly or revealed by hints.
+    pub known_positions: Vec<u8>,
+}
+
+/// A letter of the secret word revealed by [`Action::RevealHint`].
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct Hint {
+    pub position: u8,
+    pub letter: String,
+}
+
+/// A part of the wordle state streamed from an old program into a new one.
+///
+/// Chunks exported by earlier versions decode too: the version is decoded first and the rest of
+/// the chunk is upgraded from the layout of that version.
+#[derive(Encode, TypeInfo, Clone, PartialEq, Eq, Debug)]
+#[codec(crate = gstd::codec)]
+#[scale_info(crate = gstd::scale_info)]
+pub struct StateChunk {
+    /// The [`SCHEMA_VERSION`] of the program that exported the chunk.
+    pub version: u16,
+    pub games: Vec<GameState>,
+    /// The id the next started game gets.
+    pub next_game_id: GameId,
+    /// The offset of the next chunk, or [`None`] if this chunk is the last one.
+    pub next: Option<u32>,
+}
+
+impl Decode for StateChunk {
+    fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+        let version = u16::decode(input)?;
+        match version {
+            1 => Ok(legacy::StateChunkV1::decode(input)?.upgrade()),
+            2 => Ok(legacy::StateChunkV2::decode(input)?.upgrade()),
+            SCHEMA_VERSION => Ok(StateChunk {
+                version,
+                games: Decode::decode(input)?,
+                next_game_id: Decode::decode(input)?,
+                next: Decode::decode(input)?,
+            }),
+            _ => Err("Unsupported state version".into()),
+        }
+    }
 }
 
 #[derive(Encode, Decode, TypeInfo, Clone, Debug)]
@@ -45,15 +253,65 @@ pub enum Action {
 pub enum Event {
     GameStarted {
         user: ActorId,
+        game_id: GameId,
+        language: Language,
+        /// The number of letters in the secret word.
+        word_length: u8,
     },
     WordChecked {
         user: ActorId,
+        game_id: GameId,
         correct_positions: Vec<u8>,
         contained_in_word: Vec<u8>,
+        /// The secret word, revealed once the game is over.
+        secret: Option<String>,
     },
     UserWin {
         user: ActorId,
     },
+    StateExported(StateChunk),
+    StateImported {
+        /// The number of games imported, without ones skipped because they already were.
+        games: u32,
+    },
+    OwnershipTransferred {
+        previous_owner: ActorId,
+        new_owner: ActorId,
+    },
+    /// [`None`] if every letter is already known.
+    HintRevealed {
+        user: ActorId,
+        game_id: GameId,
+        hint: Option<Hint>,
+    },
+    WordsAdded {
+        language: Language,
+        list: WordList,
+        /// The number of words in the list now.
+        total: u32,
+    },
+    WordsCleared {
+        language: Language,
+        list: WordList,
+    },
+    /// The guess was rejected without using an attempt, because it isn't in the allowed list.
+    WordNotAllowed {
+        user: ActorId,
+        game_id: GameId,
+    },
+    SessionAdded(ActorId),
+    SessionRemoved(ActorId),
+    GameEnded {
+        user: ActorId,
+        game_id: GameId,
+    },
+    #[cfg(feature = "test-hooks")]
+    SecretFixed {
+        user: ActorId,
+        word: Option<String>,
+    },
+    #[cfg(feature = "test-hooks")]
+    SeedSet(u8),
 }
 
 /// Queries the contract state.
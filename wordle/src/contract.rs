@@ -0,0 +1,439 @@
+use gstd::{
+    collections::{HashMap, HashSet},
+    debug, exec, msg,
+    prelude::*,
+    ActorId,
+};
+use wordle_io::*;
+
+static mut WORDLE: Option<Wordle> = None;
+
+const BANK_OF_WORDS: [&str; 3] = ["house", "human", "horse"];
+const BANK_OF_WORDS_DE: [&str; 4] = ["apfel", "blume", "küche", "vögel"];
+const BANK_OF_WORDS_ES: [&str; 4] = ["perro", "mundo", "señal", "niñez"];
+const BANK_OF_WORDS_RU: [&str; 4] = ["слово", "книга", "место", "огонь"];
+
+fn bank_of_words(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::En => &BANK_OF_WORDS,
+        Language::De => &BANK_OF_WORDS_DE,
+        Language::Es => &BANK_OF_WORDS_ES,
+        Language::Ru => &BANK_OF_WORDS_RU,
+    }
+}
+
+/// Packed words, sorted and without duplicates so they can be binary searched.
+#[derive(Default)]
+struct WordSet(Vec<PackedWord>);
+
+impl WordSet {
+    fn extend(&mut self, mut words: Vec<PackedWord>) {
+        //只排序新的一块, 再和已经排好的单词归并去重
+        words.sort_unstable();
+        let mut merged = Vec::with_capacity(self.0.len() + words.len());
+        let mut old = self.0.iter().copied().peekable();
+        let mut new = words.into_iter().peekable();
+        while let Some(word) = match (old.peek(), new.peek()) {
+            (Some(old_word), Some(new_word)) if new_word < old_word => new.next(),
+            (Some(_), _) => old.next(),
+            (None, _) => new.next(),
+        } {
+            if merged.last() != Some(&word) {
+                merged.push(word);
+            }
+        }
+        self.0 = merged;
+    }
+
+    fn contains(&self, word: PackedWord) -> bool {
+        self.0.binary_search(&word).is_ok()
+    }
+}
+
+/// The word lists of a language.
+struct WordBank {
+    secrets: WordSet,
+    allowed: WordSet,
+}
+
+impl WordBank {
+    fn new(language: Language) -> Self {
+        let mut secrets = WordSet::default();
+        secrets.extend(
+            bank_of_words(language)
+                .iter()
+                .map(|word| language.pack(word).expect("Invalid word in the bank"))
+                .collect(),
+        );
+        Self {
+            secrets,
+            allowed: WordSet::default(),
+        }
+    }
+
+    fn list_mut(&mut self, list: WordList) -> &mut WordSet {
+        match list {
+            WordList::Secrets => &mut self.secrets,
+            WordList::Allowed => &mut self.allowed,
+        }
+    }
+
+    /// Whether `word` can be guessed in a game of this language.
+    fn allows(&self, language: Language, word: &str) -> bool {
+        self.allowed.0.is_empty()
+            || language
+                .pack(word)
+                .is_some_and(|word| self.allowed.contains(word) || self.secrets.contains(word))
+    }
+}
+
+pub struct Wordle {
+    owner: ActorId,
+    games: HashMap<GameId, Game>, // 存储每局游戏需要猜测的单词。
+    next_game_id: GameId,
+    banks: HashMap<Language, WordBank>,
+    //可以替玩家开始和进行游戏的session程序
+    sessions: HashSet<ActorId>,
+    //测试时为玩家固定的答案
+    #[cfg(feature = "test-hooks")]
+    fixed_secrets: HashMap<ActorId, String>,
+}
+
+impl Wordle {
+    /// Only the owner and added sessions can play, as they are trusted to name the right user.
+    fn assert_player(&self, source: ActorId) {
+        assert!(
+            source == self.owner || self.sessions.contains(&source),
+            "Only the owner or a session can play"
+        );
+    }
+
+    /// The secret word of a new game of `user`.
+    fn pick_word(&self, user: ActorId, language: Language) -> String {
+        #[cfg(feature = "test-hooks")]
+        if let Some(word) = self.fixed_secrets.get(&user) {
+            return word.clone();
+        }
+        #[cfg(not(feature = "test-hooks"))]
+        let _ = user;
+        let secrets = &self.banks[&language].secrets.0;
+        assert!(!secrets.is_empty(), "There are no secret words");
+        let random_id = get_random_value(secrets.len() as u32);
+        // debug!("random_id is: {:?}", random_id);
+        language
+            .unpack(secrets[random_id as usize])
+            .expect("Invalid word in the bank")
+    }
+}
+
+pub struct Game {
+    user: ActorId,
+    language: Language,
+    word: String,
+    //已经猜中或者提示过的位置, 提示不会重复
+    known_positions: Vec<u8>,
+}
+
+#[no_mangle]
+extern fn init() {
+    unsafe {
+        WORDLE = Some(Wordle {
+            owner: msg::source(),
+            games: HashMap::new(),
+            next_game_id: 0,
+            banks: [Language::En, Language::De, Language::Es, Language::Ru]
+                .into_iter()
+                .map(|language| (language, WordBank::new(language)))
+                .collect(),
+            sessions: HashSet::new(),
+            #[cfg(feature = "test-hooks")]
+            fixed_secrets: HashMap::new(),
+        })
+    }
+}
+
+#[no_mangle]
+extern fn handle() {
+    let msg = msg::load();
+    let action: Action = msg.expect("Unable to decode ");
+    debug!("wordle action is: {:?}", action);
+    let wordle = unsafe { WORDLE.as_mut().expect("The program is not initialized") };
+
+    let reply = match action {
+        Action::StartGame { user, language } => {
+            wordle.assert_player(msg::source());
+            let word = wordle.pick_word(user, language);
+            // debug!("word is: {:?}", word);
+            let game_id = wordle.next_game_id;
+            wordle.next_game_id += 1;
+            wordle.games.insert(
+                game_id,
+                Game {
+                    user,
+                    language,
+                    word: word.clone(),
+                    known_positions: vec![],
+                },
+            );
+            Event::GameStarted {
+                user,
+                game_id,
+                language,
+                word_length: word.chars().count() as u8,
+            }
+        }
+        Action::CheckWord {
+            user,
+            game_id,
+            word,
+            last_attempt,
+        } => {
+            wordle.assert_player(msg::source());
+            debug!("word is: {:?}", word);
+            let game = wordle
+                .games
+                .get_mut(&game_id)
+                .expect("There is no game with this id");
+            assert_eq!(game.user, user, "The game belongs to another user");
+            let key_word = &game.word;
+            //词库都是小写字母
+            let word = word.to_lowercase();
+            //答案总是可以猜的, 即使它不在词库里
+            if word != *key_word && !wordle.banks[&game.language].allows(game.language, &word) {
+                msg::reply(Event::WordNotAllowed { user, game_id }, 0)
+                    .expect("Error in sending a reply");
+                return;
+            }
+            let feedback = scoring::score(key_word, &word);
+
+            for i in &feedback.correct_positions {
+                if !game.known_positions.contains(i) {
+                    game.known_positions.push(*i);
+                }
+            }
+
+            //猜中或者最后一次猜测后结束游戏并公开单词
+            let solved = feedback.is_solved(key_word.chars().count());
+            let secret = if solved || last_attempt {
+                wordle.games.remove(&game_id).map(|game| game.word)
+            } else {
+                None
+            };
+
+            Event::WordChecked {
+                user,
+                game_id,
+                correct_positions: feedback.correct_positions,
+                contained_in_word: feedback.contained_in_word,
+                secret,
+            }
+        }
+        Action::RevealHint { user, game_id } => {
+            wordle.assert_player(msg::source());
+            let game = wordle
+                .games
+                .get_mut(&game_id)
+                .expect("There is no game with this id");
+            assert_eq!(game.user, user, "The game belongs to another user");
+            let hint = game
+                .word
+                .chars()
+                .enumerate()
+                .find(|(i, _)| !game.known_positions.contains(&(*i as u8)))
+                .map(|(i, letter)| Hint {
+                    position: i as u8,
+                    letter: letter.to_string(),
+                });
+            if let Some(hint) = &hint {
+                game.known_positions.push(hint.position);
+            }
+            Event::HintRevealed {
+                user,
+                game_id,
+                hint,
+            }
+        }
+        Action::ExportState { offset, limit } => {
+            assert_eq!(
+                msg::source(),
+                wordle.owner,
+                "Only the owner can export the state"
+            );
+            //按id排序保证分块导出的顺序稳定
+            let mut game_ids: Vec<GameId> = wordle.games.keys().copied().collect();
+            game_ids.sort_unstable();
+            let end = game_ids.len().min(offset.saturating_add(limit) as usize);
+            let games = game_ids
+                .get(offset as usize..end)
+                .unwrap_or_default()
+                .iter()
+                .map(|game_id| {
+                    let game = &wordle.games[game_id];
+                    GameState {
+                        game_id: *game_id,
+                        user: game.user,
+                        language: game.language,
+                        word: game.word.clone(),
+                        known_positions: game.known_positions.clone(),
+                    }
+                })
+                .collect();
+            Event::StateExported(StateChunk {
+                version: SCHEMA_VERSION,
+                games,
+                next_game_id: wordle.next_game_id,
+                next: (end < game_ids.len()).then_some(end as u32),
+            })
+        }
+        Action::ImportState(chunk) => {
+            assert_eq!(
+                msg::source(),
+                wordle.owner,
+                "Only the owner can import the state"
+            );
+            let mut games = 0;
+            for game in chunk.games {
+                //重发的分块跳过, 但不能覆盖新程序上已经开始的游戏
+                if let Some(existing) = wordle.games.get(&game.game_id) {
+                    assert!(
+                        existing.user == game.user && existing.word == game.word,
+                        "The game id is already used by another game"
+                    );
+                    continue;
+                }
+                games += 1;
+                wordle.games.insert(
+                    game.game_id,
+                    Game {
+                        user: game.user,
+                        language: game.language,
+                        word: game.word,
+                        known_positions: game.known_positions,
+                    },
+                );
+            }
+            wordle.next_game_id = wordle.next_game_id.max(chunk.next_game_id);
+            Event::StateImported { games }
+        }
+        Action::TransferOwnership { new_owner } => {
+            let previous_owner = msg::source();
+            assert_eq!(
+                previous_owner, wordle.owner,
+                "Only the owner can transfer the ownership"
+            );
+            wordle.owner = new_owner;
+            Event::OwnershipTransferred {
+                previous_owner,
+                new_owner,
+            }
+        }
+        Action::AddWords {
+            language,
+            list,
+            words,
+        } => {
+            assert_eq!(
+                msg::source(),
+                wordle.owner,
+                "Only the owner can change the word lists"
+            );
+            assert!(
+                words.iter().all(|word| language.unpack(*word).is_some()),
+                "Invalid packed word"
+            );
+            let word_set = wordle
+                .banks
+                .get_mut(&language)
+                .expect("Every language has a bank")
+                .list_mut(list);
+            word_set.extend(words);
+            Event::WordsAdded {
+                language,
+                list,
+                total: word_set.0.len() as u32,
+            }
+        }
+        Action::ClearWords { language, list } => {
+            assert_eq!(
+                msg::source(),
+                wordle.owner,
+                "Only the owner can change the word lists"
+            );
+            *wordle
+                .banks
+                .get_mut(&language)
+                .expect("Every language has a bank")
+                .list_mut(list) = WordSet::default();
+            Event::WordsCleared { language, list }
+        }
+        Action::AddSession(session) => {
+            assert_eq!(
+                msg::source(),
+                wordle.owner,
+                "Only the owner can add sessions"
+            );
+            wordle.sessions.insert(session);
+            Event::SessionAdded(session)
+        }
+        Action::RemoveSession(session) => {
+            assert_eq!(
+                msg::source(),
+                wordle.owner,
+                "Only the owner can remove sessions"
+            );
+            wordle.sessions.remove(&session);
+            Event::SessionRemoved(session)
+        }
+        Action::EndGame { user, game_id } => {
+            wordle.assert_player(msg::source());
+            //已经结束的游戏忽略, session强制结束时不知道wordle里的游戏还在不在
+            if let Some(game) = wordle.games.get(&game_id) {
+                assert_eq!(game.user, user, "The game belongs to another user");
+                wordle.games.remove(&game_id);
+            }
+            Event::GameEnded { user, game_id }
+        }
+        #[cfg(feature = "test-hooks")]
+        Action::FixSecret { user, word } => {
+            assert_eq!(
+                msg::source(),
+                wordle.owner,
+                "Only the owner can fix secrets"
+            );
+            match &word {
+                Some(word) => wordle.fixed_secrets.insert(user, word.to_lowercase()),
+                None => wordle.fixed_secrets.remove(&user),
+            };
+            Event::SecretFixed { user, word }
+        }
+        #[cfg(feature = "test-hooks")]
+        Action::SetSeed(seed) => {
+            assert_eq!(
+                msg::source(),
+                wordle.owner,
+                "Only the owner can set the seed"
+            );
+            unsafe { SEED = seed };
+            Event::SeedSet(seed)
+        }
+    };
+    msg::reply(reply, 0).expect("Error in sending a reply");
+}
+
+static mut SEED: u8 = 0;
+
+pub fn get_random_value(range: u32) -> u32 {
+    let seed = unsafe { SEED };
+    unsafe {
+        SEED = SEED.wrapping_add(1);
+        debug!("SEED is:{}", SEED);
+    };
+
+    let mut random_input: [u8; 32] = exec::program_id().into();
+    random_input[0] = random_input[0].wrapping_add(seed);
+    debug!("random_input is:{:?}", random_input);
+    let (random, _) = exec::random(random_input).expect("Error in getting random number");
+    let value = u32::from_le_bytes([random[0], random[1], random[2], random[3]]);
+    debug!("random value is:{}", value);
+    value % range
+}
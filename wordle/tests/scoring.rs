@@ -0,0 +1,138 @@
+use gstd::prelude::*;
+use gtest::{Program, System};
+use proptest::prelude::*;
+use wordle_io::{Action, Event, GameState, Language, StateChunk, SCHEMA_VERSION};
+
+const OWNER: u64 = 2;
+const USER: u64 = 3;
+
+/// A scorer written independently of the program: a letter is correct if the secret has it in
+/// the same position, and contained if the secret has it anywhere else.
+fn reference_score(secret: &str, guess: &str) -> (Vec<u8>, Vec<u8>) {
+    let secret: Vec<char> = secret.chars().collect();
+    let guess: Vec<char> = guess.chars().collect();
+    let mut correct_positions = vec![];
+    let mut contained_in_word = vec![];
+    for i in 0..secret.len().min(guess.len()) {
+        if secret[i] == guess[i] {
+            correct_positions.push(i as u8);
+        } else if secret.contains(&guess[i]) {
+            contained_in_word.push(i as u8);
+        }
+    }
+    (correct_positions, contained_in_word)
+}
+
+/// Five letter words, mostly from a small alphabet so that guesses share letters with secrets.
+fn word() -> impl Strategy<Value = String> {
+    prop_oneof![3 => "[a-e]{5}", 1 => "[a-zäöüß]{5}"]
+}
+
+/// Plays `guess` in a fresh wordle program whose only game has the `secret` word.
+fn check_word(secret: &str, guess: &str, last_attempt: bool) -> Event {
+    let system = System::new();
+    let program = Program::current_opt(&system);
+    let result = program.send_bytes(OWNER, []);
+    assert!(!result.main_failed(), "Program failed: {:?}", result);
+
+    //通过导入状态设置答案
+    let import_result = program.send(
+        OWNER,
+        Action::ImportState(StateChunk {
+            version: SCHEMA_VERSION,
+            games: vec![GameState {
+                game_id: 0,
+                user: USER.into(),
+                language: Language::De,
+                word: secret.to_string(),
+                known_positions: vec![],
+            }],
+            next_game_id: 1,
+            next: None,
+        }),
+    );
+    assert!(
+        !import_result.main_failed(),
+        "Program failed: {:?}",
+        import_result
+    );
+
+    //只有owner和session可以替玩家猜测
+    let check_result = program.send(
+        OWNER,
+        Action::CheckWord {
+            user: USER.into(),
+            game_id: 0,
+            word: guess.to_string(),
+            last_attempt,
+        },
+    );
+    assert!(
+        !check_result.main_failed(),
+        "Program failed: {:?}",
+        check_result
+    );
+    check_result
+        .log()
+        .iter()
+        .find_map(|log| Event::decode(&mut log.payload()).ok())
+        .expect("No reply from the program")
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn test_program_matches_reference(
+        secret in word(),
+        guess in word(),
+        last_attempt in any::<bool>(),
+    ) {
+        let Event::WordChecked {
+            correct_positions,
+            contained_in_word,
+            secret: revealed,
+            ..
+        } = check_word(&secret, &guess, last_attempt)
+        else {
+            panic!("unexpected reply");
+        };
+        let (expected_correct, expected_contained) = reference_score(&secret, &guess);
+        prop_assert_eq!(&correct_positions, &expected_correct);
+        prop_assert_eq!(&contained_in_word, &expected_contained);
+
+        let length = secret.chars().count();
+        prop_assert!(correct_positions.len() <= length);
+        prop_assert!(correct_positions.len() + contained_in_word.len() <= length);
+        prop_assert!(correct_positions.iter().all(|i| !contained_in_word.contains(i)));
+        //全部位置正确当且仅当猜中
+        prop_assert_eq!(correct_positions.len() == length, secret == guess);
+        //猜中或者最后一次猜测时公开答案
+        let expected_revealed = (secret == guess || last_attempt).then(|| secret.clone());
+        prop_assert_eq!(revealed, expected_revealed);
+    }
+
+    #[test]
+    fn test_solved_by_the_secret(secret in word()) {
+        let Event::WordChecked {
+            correct_positions,
+            contained_in_word,
+            secret: revealed,
+            ..
+        } = check_word(&secret, &secret, false)
+        else {
+            panic!("unexpected reply");
+        };
+        prop_assert_eq!(correct_positions, (0..5).collect::<Vec<u8>>());
+        prop_assert!(contained_in_word.is_empty());
+        prop_assert_eq!(revealed, Some(secret));
+    }
+
+    #[test]
+    fn test_scoring_matches_reference(secret in word(), guess in word()) {
+        let feedback = scoring::score(&secret, &guess);
+        let (correct_positions, contained_in_word) = reference_score(&secret, &guess);
+        prop_assert_eq!(feedback.correct_positions, correct_positions);
+        prop_assert_eq!(feedback.contained_in_word, contained_in_word);
+    }
+}
@@ -1,6 +1,6 @@
 use gstd::prelude::*;
 use gtest::{Log, Program, System};
-use wordle_io::{Action, Event};
+use wordle_io::{Action, Event, Hint, Language, StateChunk, WordList, SCHEMA_VERSION};
 
 #[test]
 fn test_start_game() {
@@ -14,7 +14,13 @@ fn test_start_game() {
 
     assert!(!result.main_failed(), "Program failed: {:?}", result);
 
-    let start_game_result = program.send(2, Action::StartGame { user: 2.into() });
+    let start_game_result = program.send(
+        2,
+        Action::StartGame {
+            user: 2.into(),
+            language: Language::En,
+        },
+    );
 
     assert!(
         !start_game_result.main_failed(),
@@ -22,8 +28,42 @@ fn test_start_game() {
         start_game_result
     );
 
-    start_game_result.contains(&Log::builder().payload(Action::StartGame { user: 2.into() }));
+    assert!(
+        start_game_result.contains(&Log::builder().payload(Event::GameStarted {
+            user: 2.into(),
+            game_id: 0,
+            language: Language::En,
+            word_length: 5,
+        })),
+        "Program failed: {:?}",
+        start_game_result
+    );
+}
+
+/// The secret word of a game, read from the exported state. The owner is 2.
+fn secret_of(program: &Program, game_id: u64) -> String {
+    let export_result = program.send(
+        2,
+        Action::ExportState {
+            offset: 0,
+            limit: 100,
+        },
+    );
+    export_result
+        .log()
+        .iter()
+        .find_map(|log| match Event::decode(&mut log.payload()) {
+            Ok(Event::StateExported(chunk)) => Some(chunk),
+            _ => None,
+        })
+        .expect("no state exported")
+        .games
+        .into_iter()
+        .find(|game| game.game_id == game_id)
+        .expect("the game should be open")
+        .word
 }
+
 #[test]
 fn test_wordle_game_success() {
     let system = System::new();
@@ -36,7 +76,13 @@ fn test_wordle_game_success() {
 
     assert!(!result.main_failed(), "Program failed: {:?}", result);
 
-    let start_game_result = program.send(2, Action::StartGame { user: 2.into() });
+    let start_game_result = program.send(
+        2,
+        Action::StartGame {
+            user: 2.into(),
+            language: Language::En,
+        },
+    );
 
     assert!(
         !start_game_result.main_failed(),
@@ -45,14 +91,23 @@ fn test_wordle_game_success() {
     );
 
     assert!(
-        start_game_result.contains(&Log::builder().payload(Action::StartGame { user: 2.into() }))
+        start_game_result.contains(&Log::builder().payload(Event::GameStarted {
+            user: 2.into(),
+            game_id: 0,
+            language: Language::En,
+            word_length: 5,
+        }))
     );
 
+    //答案是随机选的, 从导出的状态里读出来
+    let secret = secret_of(&program, 0);
     let wordle_result = program.send(
         2,
         Action::CheckWord {
             user: 2.into(),
-            word: "house".to_string(),
+            game_id: 0,
+            word: secret.clone(),
+            last_attempt: false,
         },
     );
     assert!(
@@ -63,8 +118,10 @@ fn test_wordle_game_success() {
     assert!(
         wordle_result.contains(&Log::builder().payload(Event::WordChecked {
             user: 2.into(),
+            game_id: 0,
             correct_positions: vec![0, 1, 2, 3, 4],
             contained_in_word: vec![],
+            secret: Some(secret),
         }))
     );
     let result_event = wordle_result.decoded_log::<Event>();
@@ -83,7 +140,13 @@ fn test_wordle_game_more_times() {
 
     assert!(!result.main_failed(), "Program failed: {:?}", result);
 
-    let start_game_result = program.send(2, Action::StartGame { user: 2.into() });
+    let start_game_result = program.send(
+        2,
+        Action::StartGame {
+            user: 2.into(),
+            language: Language::En,
+        },
+    );
 
     assert!(
         !start_game_result.main_failed(),
@@ -92,14 +155,51 @@ fn test_wordle_game_more_times() {
     );
 
     assert!(
-        start_game_result.contains(&Log::builder().payload(Action::StartGame { user: 2.into() }))
+        start_game_result.contains(&Log::builder().payload(Event::GameStarted {
+            user: 2.into(),
+            game_id: 0,
+            language: Language::En,
+            word_length: 5,
+        }))
     );
 
+    //答案是随机选的, 从导出的状态里读出来, 猜错的反馈按规则算
+    let secret = secret_of(&program, 0);
+    for word in ["human", "heuan"] {
+        let wordle_result = program.send(
+            2,
+            Action::CheckWord {
+                user: 2.into(),
+                game_id: 0,
+                word: word.to_string(),
+                last_attempt: false,
+            },
+        );
+        let result_event = wordle_result.decoded_log::<Event>();
+        println!("word result is:{:?}", result_event);
+        assert!(
+            !wordle_result.main_failed(),
+            "Program failed: {:?}",
+            wordle_result
+        );
+        let feedback = scoring::score(&secret, word);
+        assert!(
+            wordle_result.contains(&Log::builder().payload(Event::WordChecked {
+                user: 2.into(),
+                game_id: 0,
+                correct_positions: feedback.correct_positions,
+                contained_in_word: feedback.contained_in_word,
+                secret: None,
+            }))
+        );
+    }
     let wordle_result = program.send(
         2,
         Action::CheckWord {
             user: 2.into(),
-            word: "human".to_string(),
+            game_id: 0,
+            word: secret.clone(),
+            last_attempt: false,
         },
     );
     let result_event = wordle_result.decoded_log::<Event>();
@@ -112,51 +212,848 @@ fn test_wordle_game_more_times() {
     assert!(
         wordle_result.contains(&Log::builder().payload(Event::WordChecked {
             user: 2.into(),
-            correct_positions: vec![0],
-            contained_in_word: vec![1],
+            game_id: 0,
+            correct_positions: vec![0, 1, 2, 3, 4],
+            contained_in_word: vec![],
+            secret: Some(secret),
         }))
     );
+}
 
-    let wordle_result = program.send(
+#[test]
+fn test_wordle_game_unicode() {
+    let system = System::new();
+
+    system.init_logger();
+
+    let program = Program::current_opt(&system);
+
+    let result = program.send_bytes(2, []);
+
+    assert!(!result.main_failed(), "Program failed: {:?}", result);
+
+    let start_game_result = program.send(
         2,
-        Action::CheckWord {
+        Action::StartGame {
             user: 2.into(),
-            word: "heuan".to_string(),
+            language: Language::Ru,
+        },
+    );
+    assert!(
+        !start_game_result.main_failed(),
+        "Program failed: {:?}",
+        start_game_result
+    );
+
+    //大写的猜测也要和小写的单词匹配, 位置按字符计算
+    let mut solved = false;
+    for word in ["СЛОВО", "КНИГА", "МЕСТО", "ОГОНЬ"] {
+        let wordle_result = program.send(
+            2,
+            Action::CheckWord {
+                user: 2.into(),
+                game_id: 0,
+                word: word.to_string(),
+                last_attempt: false,
+            },
+        );
+        assert!(
+            !wordle_result.main_failed(),
+            "Program failed: {:?}",
+            wordle_result
+        );
+        if wordle_result.contains(&Log::builder().payload(Event::WordChecked {
+            user: 2.into(),
+            game_id: 0,
+            correct_positions: vec![0, 1, 2, 3, 4],
+            contained_in_word: vec![],
+            secret: Some(word.to_lowercase()),
+        })) {
+            solved = true;
+            break;
+        }
+    }
+    assert!(solved, "one of the bank words must be the secret");
+}
+
+#[test]
+fn test_migrate_wordle() {
+    let system = System::new();
+
+    system.init_logger();
+
+    let program = Program::current_opt(&system);
+
+    let result = program.send_bytes(2, []);
+
+    assert!(!result.main_failed(), "Program failed: {:?}", result);
+
+    for _ in 0..2 {
+        let start_game_result = program.send(
+            2,
+            Action::StartGame {
+                user: 3.into(),
+                language: Language::En,
+            },
+        );
+        assert!(
+            !start_game_result.main_failed(),
+            "Program failed: {:?}",
+            start_game_result
+        );
+    }
+
+    //只有owner可以导出状态
+    let forbidden_result = program.send(
+        3,
+        Action::ExportState {
+            offset: 0,
+            limit: 1,
+        },
+    );
+    assert!(forbidden_result.main_failed());
+
+    let new_program = Program::current_opt(&system);
+    let result = new_program.send_bytes(2, []);
+    assert!(!result.main_failed(), "Program failed: {:?}", result);
+
+    let mut offset = Some(0);
+    let mut chunks = vec![];
+    while let Some(next) = offset {
+        let export_result = program.send(
+            2,
+            Action::ExportState {
+                offset: next,
+                limit: 1,
+            },
+        );
+        assert!(
+            !export_result.main_failed(),
+            "Program failed: {:?}",
+            export_result
+        );
+        let chunk = export_result
+            .log()
+            .iter()
+            .find_map(|log| match Event::decode(&mut log.payload()) {
+                Ok(Event::StateExported(chunk)) => Some(chunk),
+                _ => None,
+            })
+            .expect("no state exported");
+        offset = chunk.next;
+        chunks.push(chunk);
+    }
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks
+        .iter()
+        .all(|chunk: &StateChunk| chunk.version == SCHEMA_VERSION && chunk.next_game_id == 2));
+
+    let first_chunk = chunks[0].clone();
+    for chunk in chunks {
+        let import_result = new_program.send(2, Action::ImportState(chunk));
+        assert!(
+            import_result.contains(&Log::builder().payload(Event::StateImported { games: 1 })),
+            "Program failed: {:?}",
+            import_result
+        );
+    }
+    //重发的分块不会重复导入
+    let import_result = new_program.send(2, Action::ImportState(first_chunk.clone()));
+    assert!(
+        import_result.contains(&Log::builder().payload(Event::StateImported { games: 0 })),
+        "Program failed: {:?}",
+        import_result
+    );
+
+    //迁移后的游戏可以继续, 新游戏的id不会重复
+    let wordle_result = new_program.send(
+        2,
+        Action::CheckWord {
+            user: 3.into(),
+            game_id: 1,
+            word: "house".to_string(),
+            last_attempt: false,
         },
     );
-    let result_event = wordle_result.decoded_log::<Event>();
-    println!("word result is:{:?}", result_event);
     assert!(
         !wordle_result.main_failed(),
         "Program failed: {:?}",
         wordle_result
     );
+    let start_game_result = new_program.send(
+        2,
+        Action::StartGame {
+            user: 3.into(),
+            language: Language::En,
+        },
+    );
+    let started = start_game_result.log().iter().any(|log| {
+        matches!(
+            Event::decode(&mut log.payload()),
+            Ok(Event::GameStarted { game_id: 2, .. })
+        )
+    });
+    assert!(started, "Program failed: {:?}", start_game_result);
+
+    //不能用导入覆盖新程序上已经开始的游戏
+    let mut colliding_chunk = first_chunk;
+    colliding_chunk.games[0].game_id = 2;
+    colliding_chunk.games[0].user = 4.into();
+    let import_result = new_program.send(2, Action::ImportState(colliding_chunk));
+    assert!(import_result.main_failed(), "the game 2 was overwritten");
+}
+
+#[test]
+fn test_import_legacy_state() {
+    let system = System::new();
+
+    system.init_logger();
+
+    let program = Program::current_opt(&system);
+
+    let result = program.send_bytes(2, []);
+
+    assert!(!result.main_failed(), "Program failed: {:?}", result);
+
+    //旧版本导出的分块只有版本号开头是一样的
+    let empty_chunk = StateChunk {
+        version: SCHEMA_VERSION,
+        games: vec![],
+        next_game_id: 0,
+        next: None,
+    };
+    let import_index = Action::ImportState(empty_chunk).encode()[0];
+    let user: gstd::ActorId = 3.into();
+    let v1_chunk = (
+        1u16,
+        vec![(0u64, user, "horse".to_string())],
+        1u64,
+        None::<u32>,
+    );
+    let v2_chunk = (
+        2u16,
+        vec![(1u64, user, "niñez".to_string(), vec![0u8])],
+        2u64,
+        None::<u32>,
+    );
+    for chunk in [v1_chunk.encode(), v2_chunk.encode()] {
+        let import_result = program.send_bytes(2, [vec![import_index], chunk].concat());
+        assert!(
+            import_result.contains(&Log::builder().payload(Event::StateImported { games: 1 })),
+            "Program failed: {:?}",
+            import_result
+        );
+    }
+
+    let wordle_result = program.send(
+        2,
+        Action::CheckWord {
+            user,
+            game_id: 0,
+            word: "house".to_string(),
+            last_attempt: false,
+        },
+    );
     assert!(
         wordle_result.contains(&Log::builder().payload(Event::WordChecked {
+            user,
+            game_id: 0,
+            correct_positions: vec![0, 1, 3, 4],
+            contained_in_word: vec![],
+            secret: None,
+        })),
+        "Program failed: {:?}",
+        wordle_result
+    );
+    //第一个字母已经知道了, 提示下一个
+    let hint_result = program.send(2, Action::RevealHint { user, game_id: 1 });
+    assert!(
+        hint_result.contains(&Log::builder().payload(Event::HintRevealed {
+            user,
+            game_id: 1,
+            hint: Some(Hint {
+                position: 1,
+                letter: "i".to_string(),
+            }),
+        })),
+        "Program failed: {:?}",
+        hint_result
+    );
+    let start_game_result = program.send(
+        2,
+        Action::StartGame {
+            user,
+            language: Language::En,
+        },
+    );
+    assert!(
+        start_game_result.contains(&Log::builder().payload(Event::GameStarted {
+            user,
+            game_id: 2,
+            language: Language::En,
+            word_length: 5,
+        })),
+        "Program failed: {:?}",
+        start_game_result
+    );
+}
+
+#[test]
+fn test_transfer_ownership() {
+    let system = System::new();
+
+    system.init_logger();
+
+    let program = Program::current_opt(&system);
+
+    let result = program.send_bytes(2, []);
+
+    assert!(!result.main_failed(), "Program failed: {:?}", result);
+
+    let transfer_result = program.send(
+        2,
+        Action::TransferOwnership {
+            new_owner: 3.into(),
+        },
+    );
+    assert!(
+        transfer_result.contains(&Log::builder().payload(Event::OwnershipTransferred {
+            previous_owner: 2.into(),
+            new_owner: 3.into(),
+        })),
+        "Program failed: {:?}",
+        transfer_result
+    );
+
+    //原来的owner不能再导出状态
+    let export_result = program.send(
+        2,
+        Action::ExportState {
+            offset: 0,
+            limit: 1,
+        },
+    );
+    assert!(export_result.main_failed());
+    let export_result = program.send(
+        3,
+        Action::ExportState {
+            offset: 0,
+            limit: 1,
+        },
+    );
+    assert!(
+        !export_result.main_failed(),
+        "Program failed: {:?}",
+        export_result
+    );
+}
+
+#[test]
+fn test_sessions() {
+    let system = System::new();
+
+    system.init_logger();
+
+    let program = Program::current_opt(&system);
+
+    let result = program.send_bytes(2, []);
+
+    assert!(!result.main_failed(), "Program failed: {:?}", result);
+
+    let start_game = |source: u64| {
+        program.send(
+            source,
+            Action::StartGame {
+                user: 3.into(),
+                language: Language::En,
+            },
+        )
+    };
+    //玩家自己和没有添加的session都不能开始游戏
+    for source in [3, 4] {
+        let start_game_result = start_game(source);
+        assert!(
+            start_game_result.main_failed(),
+            "{source} started a game: {:?}",
+            start_game_result
+        );
+    }
+    let forbidden_result = program.send(4, Action::AddSession(4.into()));
+    assert!(forbidden_result.main_failed());
+
+    let add_result = program.send(2, Action::AddSession(4.into()));
+    assert!(
+        add_result.contains(&Log::builder().payload(Event::SessionAdded(4.into()))),
+        "Program failed: {:?}",
+        add_result
+    );
+    let start_game_result = start_game(4);
+    assert!(
+        start_game_result.contains(&Log::builder().payload(Event::GameStarted {
+            user: 3.into(),
+            game_id: 0,
+            language: Language::En,
+            word_length: 5,
+        })),
+        "Program failed: {:?}",
+        start_game_result
+    );
+    let hint_result = program.send(
+        3,
+        Action::RevealHint {
+            user: 3.into(),
+            game_id: 0,
+        },
+    );
+    assert!(hint_result.main_failed(), "the player revealed a hint");
+
+    //结束游戏后不能再猜, 再次结束也可以
+    for _ in 0..2 {
+        let end_result = program.send(
+            4,
+            Action::EndGame {
+                user: 3.into(),
+                game_id: 0,
+            },
+        );
+        assert!(
+            end_result.contains(&Log::builder().payload(Event::GameEnded {
+                user: 3.into(),
+                game_id: 0,
+            })),
+            "Program failed: {:?}",
+            end_result
+        );
+    }
+    let wordle_result = program.send(
+        4,
+        Action::CheckWord {
+            user: 3.into(),
+            game_id: 0,
+            word: "house".to_string(),
+            last_attempt: false,
+        },
+    );
+    assert!(wordle_result.main_failed(), "the game is still open");
+
+    let remove_result = program.send(2, Action::RemoveSession(4.into()));
+    assert!(
+        remove_result.contains(&Log::builder().payload(Event::SessionRemoved(4.into()))),
+        "Program failed: {:?}",
+        remove_result
+    );
+    let start_game_result = start_game(4);
+    assert!(
+        start_game_result.main_failed(),
+        "the session wasn't removed"
+    );
+}
+
+#[test]
+fn test_reveal_hint() {
+    let system = System::new();
+
+    system.init_logger();
+
+    let program = Program::current_opt(&system);
+
+    let result = program.send_bytes(2, []);
+
+    assert!(!result.main_failed(), "Program failed: {:?}", result);
+
+    let start_game_result = program.send(
+        2,
+        Action::StartGame {
             user: 2.into(),
-            correct_positions: vec![0, 2],
-            contained_in_word: vec![1],
-        }))
+            language: Language::En,
+        },
     );
+    assert!(
+        !start_game_result.main_failed(),
+        "Program failed: {:?}",
+        start_game_result
+    );
+
     let wordle_result = program.send(
         2,
         Action::CheckWord {
             user: 2.into(),
-            word: "house".to_string(),
+            game_id: 0,
+            word: "human".to_string(),
+            last_attempt: false,
         },
     );
-    let result_event = wordle_result.decoded_log::<Event>();
-    println!("word result is:{:?}", result_event);
     assert!(
         !wordle_result.main_failed(),
         "Program failed: {:?}",
         wordle_result
     );
+
+    //第0个位置已经猜中, 提示从第1个位置开始且不重复
+    for (position, letter) in [(1, "o"), (2, "u")] {
+        let hint_result = program.send(
+            2,
+            Action::RevealHint {
+                user: 2.into(),
+                game_id: 0,
+            },
+        );
+        assert!(
+            hint_result.contains(&Log::builder().payload(Event::HintRevealed {
+                user: 2.into(),
+                game_id: 0,
+                hint: Some(Hint {
+                    position,
+                    letter: letter.to_string(),
+                }),
+            })),
+            "Program failed: {:?}",
+            hint_result
+        );
+    }
+}
+
+#[test]
+fn test_pack_words() {
+    for (language, word) in [
+        (Language::En, "house"),
+        (Language::De, "küche"),
+        (Language::Es, "niñez"),
+        (Language::Ru, "огонь"),
+    ] {
+        let packed = language.pack(word).expect("a word of the language");
+        assert_eq!(language.unpack(packed), Some(word.to_string()));
+    }
+    //打包后的顺序和单词的顺序一致
+    let en = Language::En;
+    assert_eq!(en.pack("aaaaa"), Some(0));
+    assert!(en.pack("house") < en.pack("human"));
+    assert_eq!(en.pack("hous"), None);
+    assert_eq!(en.pack("houses"), None);
+    assert_eq!(en.pack("hoüse"), None);
+    assert_eq!(en.unpack(26u32.pow(5)), None);
+}
+
+#[test]
+fn test_word_lists() {
+    let system = System::new();
+
+    system.init_logger();
+
+    let program = Program::current_opt(&system);
+
+    let result = program.send_bytes(2, []);
+
+    assert!(!result.main_failed(), "Program failed: {:?}", result);
+
+    let pack = |word| Language::En.pack(word).expect("an English word");
+    //只有owner可以修改单词列表
+    let forbidden_result = program.send(
+        3,
+        Action::AddWords {
+            language: Language::En,
+            list: WordList::Allowed,
+            words: vec![pack("crane")],
+        },
+    );
+    assert!(forbidden_result.main_failed());
+
+    //分块上传, 重复的单词只存一次
+    for (words, total) in [(vec!["crane", "slate"], 2), (vec!["slate", "mouse"], 3)] {
+        let add_result = program.send(
+            2,
+            Action::AddWords {
+                language: Language::En,
+                list: WordList::Allowed,
+                words: words.into_iter().map(pack).collect(),
+            },
+        );
+        assert!(
+            add_result.contains(&Log::builder().payload(Event::WordsAdded {
+                language: Language::En,
+                list: WordList::Allowed,
+                total,
+            })),
+            "Program failed: {:?}",
+            add_result
+        );
+    }
+    let add_result = program.send(
+        2,
+        Action::AddWords {
+            language: Language::En,
+            list: WordList::Allowed,
+            words: vec![26u32.pow(5)],
+        },
+    );
     assert!(
-        wordle_result.contains(&Log::builder().payload(Event::WordChecked {
-            user: 2.into(),
+        add_result.main_failed(),
+        "an invalid packed word is rejected"
+    );
+
+    //新的答案列表只有一个单词
+    let clear_result = program.send(
+        2,
+        Action::ClearWords {
+            language: Language::En,
+            list: WordList::Secrets,
+        },
+    );
+    assert!(
+        !clear_result.main_failed(),
+        "Program failed: {:?}",
+        clear_result
+    );
+    let add_result = program.send(
+        2,
+        Action::AddWords {
+            language: Language::En,
+            list: WordList::Secrets,
+            words: vec![pack("plant")],
+        },
+    );
+    assert!(
+        !add_result.main_failed(),
+        "Program failed: {:?}",
+        add_result
+    );
+    let start_game_result = program.send(
+        2,
+        Action::StartGame {
+            user: 3.into(),
+            language: Language::En,
+        },
+    );
+    assert!(
+        !start_game_result.main_failed(),
+        "Program failed: {:?}",
+        start_game_result
+    );
+
+    //不在列表里的单词被拒绝, 列表里的单词和答案都可以猜
+    let check = |word: &str| {
+        program.send(
+            2,
+            Action::CheckWord {
+                user: 3.into(),
+                game_id: 0,
+                word: word.to_string(),
+                last_attempt: false,
+            },
+        )
+    };
+    let rejected_result = check("house");
+    assert!(
+        rejected_result.contains(&Log::builder().payload(Event::WordNotAllowed {
+            user: 3.into(),
+            game_id: 0,
+        })),
+        "Program failed: {:?}",
+        rejected_result
+    );
+    let checked_result = check("slate");
+    assert!(
+        checked_result.contains(&Log::builder().payload(Event::WordChecked {
+            user: 3.into(),
+            game_id: 0,
+            correct_positions: vec![1, 2],
+            contained_in_word: vec![3],
+            secret: None,
+        })),
+        "Program failed: {:?}",
+        checked_result
+    );
+    let solved_result = check("plant");
+    assert!(
+        solved_result.contains(&Log::builder().payload(Event::WordChecked {
+            user: 3.into(),
+            game_id: 0,
             correct_positions: vec![0, 1, 2, 3, 4],
             contained_in_word: vec![],
-        }))
+            secret: Some("plant".to_string()),
+        })),
+        "Program failed: {:?}",
+        solved_result
+    );
+}
+
+#[cfg(feature = "test-hooks")]
+#[test]
+fn test_fixed_secret() {
+    let system = System::new();
+
+    system.init_logger();
+
+    let program = Program::current_opt(&system);
+
+    let result = program.send_bytes(2, []);
+
+    assert!(!result.main_failed(), "Program failed: {:?}", result);
+
+    //只有所有者可以固定答案
+    let fix_result = program.send(
+        3,
+        Action::FixSecret {
+            user: 3.into(),
+            word: Some("crane".to_string()),
+        },
+    );
+    assert!(fix_result.main_failed());
+
+    let fix_result = program.send(
+        2,
+        Action::FixSecret {
+            user: 3.into(),
+            word: Some("Crane".to_string()),
+        },
+    );
+    assert!(
+        fix_result.contains(&Log::builder().payload(Event::SecretFixed {
+            user: 3.into(),
+            word: Some("Crane".to_string()),
+        })),
+        "Program failed: {:?}",
+        fix_result
+    );
+
+    //固定的答案不受语言和词库限制
+    let start_game_result = program.send(
+        2,
+        Action::StartGame {
+            user: 3.into(),
+            language: Language::De,
+        },
+    );
+    assert!(
+        start_game_result.contains(&Log::builder().payload(Event::GameStarted {
+            user: 3.into(),
+            game_id: 0,
+            language: Language::De,
+            word_length: 5,
+        })),
+        "Program failed: {:?}",
+        start_game_result
     );
+
+    let wordle_result = program.send(
+        2,
+        Action::CheckWord {
+            user: 3.into(),
+            game_id: 0,
+            word: "react".to_string(),
+            last_attempt: false,
+        },
+    );
+    assert!(
+        wordle_result.contains(&Log::builder().payload(Event::WordChecked {
+            user: 3.into(),
+            game_id: 0,
+            correct_positions: vec![2],
+            contained_in_word: vec![0, 1, 3],
+            secret: None,
+        })),
+        "Program failed: {:?}",
+        wordle_result
+    );
+
+    let clear_result = program.send(
+        2,
+        Action::FixSecret {
+            user: 3.into(),
+            word: None,
+        },
+    );
+    assert!(
+        !clear_result.main_failed(),
+        "Program failed: {:?}",
+        clear_result
+    );
+    let start_game_result = program.send(
+        2,
+        Action::StartGame {
+            user: 3.into(),
+            language: Language::En,
+        },
+    );
+    assert!(
+        !start_game_result.main_failed(),
+        "Program failed: {:?}",
+        start_game_result
+    );
+    let export_result = program.send(
+        2,
+        Action::ExportState {
+            offset: 1,
+            limit: 1,
+        },
+    );
+    let games = export_result
+        .log()
+        .iter()
+        .find_map(|log| match Event::decode(&mut log.payload()) {
+            Ok(Event::StateExported(chunk)) => Some(chunk.games),
+            _ => None,
+        })
+        .expect("Program failed");
+    assert!(["house", "human", "horse"].contains(&games[0].word.as_str()));
+}
+
+#[cfg(feature = "test-hooks")]
+#[test]
+fn test_set_seed() {
+    let system = System::new();
+
+    system.init_logger();
+
+    let program = Program::current_opt(&system);
+
+    let result = program.send_bytes(2, []);
+
+    assert!(!result.main_failed(), "Program failed: {:?}", result);
+
+    //只有所有者可以设置种子
+    let seed_result = program.send(3, Action::SetSeed(7));
+    assert!(seed_result.main_failed());
+
+    //同一个种子选出同样的单词, 最后一次猜测会公开答案
+    let mut secrets = vec![];
+    for game_id in 0..2 {
+        let seed_result = program.send(2, Action::SetSeed(7));
+        assert!(
+            seed_result.contains(&Log::builder().payload(Event::SeedSet(7))),
+            "Program failed: {:?}",
+            seed_result
+        );
+        let start_game_result = program.send(
+            2,
+            Action::StartGame {
+                user: 2.into(),
+                language: Language::En,
+            },
+        );
+        assert!(
+            !start_game_result.main_failed(),
+            "Program failed: {:?}",
+            start_game_result
+        );
+        let wordle_result = program.send(
+            2,
+            Action::CheckWord {
+                user: 2.into(),
+                game_id,
+                word: "house".to_string(),
+                last_attempt: true,
+            },
+        );
+        let secret = wordle_result
+            .decoded_log::<Event>()
+            .into_iter()
+            .find_map(|log| match log.payload() {
+                Event::WordChecked { secret, .. } => secret.clone(),
+                _ => None,
+            })
+            .expect("the last attempt should reveal the secret");
+        secrets.push(secret);
+    }
+    assert_eq!(secrets[0], secrets[1]);
 }
@@ -0,0 +1,204 @@
+//! Automation for the workspace, run as `cargo xtask <command>`.
+
+use anyhow::{anyhow, bail, Result};
+use gclient::{EventListener, EventProcessor, GearApi, WSAddress};
+use gstd::{ActorId, Encode};
+use session_io::GameSessionInit;
+use std::{env, path::PathBuf};
+use xshell::{cmd, Shell};
+
+const USAGE: &str = "\
+Usage: cargo xtask <command>
+
+Commands:
+    node                 Downloads the node binary the gclient tests run.
+    build                Builds the wasm of the programs.
+    test [gclient]       Runs the gtest suites, or the gclient ones on a local node.
+    ci                   Checks formatting and lints, then runs all tests.
+    deploy [options]     Uploads the wordle program, then the session playing on it.
+        --node <url>             The node to deploy to [default: ws://127.0.0.1:9944].
+        --suri <suri>            The deploying account [default: //Alice].
+        --max-play-times <n>     The attempts of a game [default: 6].
+    addresses            Prints the addresses of the last deployment.";
+
+/// The node release matching the `gclient` version of the workspace.
+const NODE_VERSION: &str = "1.4.2";
+/// Where `node` puts the node binary, relative to the workspace root.
+const NODE_PATH: &str = "target/tmp/gear";
+/// Where `deploy` records the addresses, in the format of `publish.md`.
+const ADDRESSES_PATH: &str = "target/deployed.txt";
+
+fn main() -> Result<()> {
+    let sh = Shell::new()?;
+    sh.change_dir(workspace_root());
+    let mut args = env::args().skip(1);
+    let command = args.next();
+    let args: Vec<String> = args.collect();
+    match command.as_deref() {
+        Some("node") => node(&sh),
+        Some("build") => build(&sh),
+        Some("test") => test(&sh, &args),
+        Some("ci") => ci(&sh),
+        Some("deploy") => deploy(&sh, &args),
+        Some("addresses") => addresses(&sh),
+        _ => bail!("{USAGE}"),
+    }
+}
+
+fn workspace_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask is in the workspace")
+        .to_path_buf()
+}
+
+fn node(sh: &Shell) -> Result<()> {
+    let url = format!("https://get.gear.rs/gear-v{NODE_VERSION}-x86_64-unknown-linux-gnu.tar.xz");
+    sh.create_dir("target/tmp")?;
+    cmd!(sh, "curl -fsSL {url} -o target/tmp/gear.tar.xz").run()?;
+    cmd!(sh, "tar -xJf target/tmp/gear.tar.xz -C target/tmp").run()?;
+    sh.remove_path("target/tmp/gear.tar.xz")?;
+    println!("The node is at {NODE_PATH}");
+    Ok(())
+}
+
+fn build(sh: &Shell) -> Result<()> {
+    cmd!(sh, "cargo build --release -p wordle -p session").run()?;
+    for program in ["wordle", "session"] {
+        println!("target/wasm32-unknown-unknown/release/{program}.opt.wasm");
+    }
+    Ok(())
+}
+
+fn test(sh: &Shell, args: &[String]) -> Result<()> {
+    match args {
+        [] => cmd!(sh, "cargo test --workspace -- --skip gclient").run()?,
+        [suite] if suite == "gclient" => {
+            if !sh.path_exists(NODE_PATH) {
+                bail!("There is no node at {NODE_PATH}, run `cargo xtask node` first");
+            }
+            cmd!(sh, "cargo test --workspace gclient").run()?;
+        }
+        _ => bail!("{USAGE}"),
+    }
+    Ok(())
+}
+
+fn ci(sh: &Shell) -> Result<()> {
+    cmd!(sh, "cargo fmt --all --check").run()?;
+    cmd!(
+        sh,
+        "cargo clippy --workspace --all-targets -- -D warnings -A unused-imports"
+    )
+    .run()?;
+    test(sh, &[])?;
+    //没有节点时只跑gtest
+    if sh.path_exists(NODE_PATH) {
+        test(sh, &["gclient".to_string()])?;
+    } else {
+        println!("Skipped the gclient tests, run `cargo xtask node` to include them");
+    }
+    Ok(())
+}
+
+fn deploy(sh: &Shell, args: &[String]) -> Result<()> {
+    let mut node = "ws://127.0.0.1:9944".to_string();
+    let mut suri = "//Alice".to_string();
+    let mut max_play_times = 6;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = iter
+            .next()
+            .ok_or_else(|| anyhow!("{arg} needs a value\n{USAGE}"))?;
+        match arg.as_str() {
+            "--node" => node = value.clone(),
+            "--suri" => suri = value.clone(),
+            "--max-play-times" => max_play_times = value.parse()?,
+            _ => bail!("Unknown argument {arg}\n{USAGE}"),
+        }
+    }
+
+    let (wordle, session) = tokio::runtime::Runtime::new()?.block_on(async {
+        let (domain, port) = node
+            .rsplit_once(':')
+            .ok_or_else(|| anyhow!("The node URL {node} has no port"))?;
+        let api = GearApi::init_with(WSAddress::new(domain, port.parse::<u16>()?), &suri).await?;
+        let mut listener = api.subscribe().await?;
+
+        //先部署wordle, session初始化时需要它的地址
+        let wordle = upload(&api, &mut listener, wordle::WASM_BINARY, vec![]).await?;
+        println!("Deployed wordle");
+        let init = GameSessionInit {
+            wordle_address: ActorId::new(wordle),
+            max_play_times,
+        };
+        let session = upload(&api, &mut listener, session::WASM_BINARY, init.encode()).await?;
+        println!("Deployed session");
+        //wordle只接受所有者和添加过的session开始游戏
+        let action = wordle_io::Action::AddSession(ActorId::new(session));
+        send(&api, &mut listener, wordle, action.encode()).await?;
+        println!("Added the session to wordle");
+        anyhow::Ok((wordle, session))
+    })?;
+
+    let addresses = format!(
+        "wordle:0x{}\nsession:0x{}\n",
+        hex::encode(wordle),
+        hex::encode(session)
+    );
+    sh.write_file(ADDRESSES_PATH, &addresses)?;
+    print!("{addresses}");
+    Ok(())
+}
+
+/// Uploads `code` and waits for its initialization, returning the address of the program.
+async fn upload(
+    api: &GearApi,
+    listener: &mut EventListener,
+    code: &[u8],
+    payload: Vec<u8>,
+) -> Result<[u8; 32]> {
+    let gas_info = api
+        .calculate_upload_gas(None, code.to_vec(), payload.clone(), 0, true)
+        .await?;
+    let (message_id, program_id, _) = api
+        .upload_program_bytes(
+            code,
+            gclient::now_micros().to_le_bytes(),
+            payload,
+            gas_info.min_limit,
+            0,
+        )
+        .await?;
+    if !listener.message_processed(message_id).await?.succeed() {
+        bail!("The program failed to initialize");
+    }
+    Ok(program_id.as_ref().try_into()?)
+}
+
+/// Sends `payload` to `program` and waits for it to be processed.
+async fn send(
+    api: &GearApi,
+    listener: &mut EventListener,
+    program: [u8; 32],
+    payload: Vec<u8>,
+) -> Result<()> {
+    let gas_info = api
+        .calculate_handle_gas(None, program.into(), payload.clone(), 0, true)
+        .await?;
+    let (message_id, _) = api
+        .send_message_bytes(program.into(), payload, gas_info.min_limit, 0)
+        .await?;
+    if !listener.message_processed(message_id).await?.succeed() {
+        bail!("The program failed to process the message");
+    }
+    Ok(())
+}
+
+fn addresses(sh: &Shell) -> Result<()> {
+    if !sh.path_exists(ADDRESSES_PATH) {
+        bail!("Nothing was deployed yet, run `cargo xtask deploy` first");
+    }
+    print!("{}", sh.read_file(ADDRESSES_PATH)?);
+    Ok(())
+}